@@ -1,11 +1,24 @@
+use actix_web::middleware::Compress;
 use actix_web::web::Data;
 use actix_web::{main, App, HttpServer};
 use thiserror::Error;
 
+use std::env::args;
 use std::io::Error as IoError;
 
+use crate::models::runs::{Run, RunError};
+use crate::routes::accounts::create_account_route;
+use crate::routes::analytics::analytics_route;
 use crate::routes::authentication::authentication_scope;
+use crate::routes::health::readiness_route;
+use crate::routes::profiles::profiles_scope;
+use crate::routes::runs::runs_scope;
+use crate::routes::schedule::upcoming_route;
+use crate::routes::openapi::{openapi_service, openapi_spec};
+use crate::routes::status::status_route;
 use crate::utils::application::context::{AppContext, AppContextError};
+use crate::utils::application::logging::{init_logging, LoggingError};
+use crate::utils::extractors::json::json_config;
 
 mod models;
 mod routes;
@@ -20,21 +33,69 @@ enum AppError {
     Server(#[from] IoError),
 
     #[error("Couldn't load App Context, {0:#}")]
-    Context(#[from] AppContextError)
+    Context(#[from] AppContextError),
+
+    #[error("Couldn't initialize logging, {0:#}")]
+    Logging(#[from] LoggingError),
+
+    #[error("Error while sweeping interrupted runs, {0:#}")]
+    InterruptedRuns(#[from] RunError)
 }
 
 #[main]
 async fn main() -> Result<(), AppError> {
+    init_logging()?;
+
     let context = AppContext::new().await?;
 
-    HttpServer::new(move || {
+    // The admin account is synced while creating the
+    // context, `--seed-admin` exits right after it so
+    // fresh installs can be bootstrapped without serving.
+    if args().any(|argument| argument == "--seed-admin") {
+        log::info!("The admin account is seeded, exiting.");
+        return Ok(());
+    }
+
+    // Only the serving process may sweep, any run left
+    // unfinished belongs to a process that is gone.
+    let interrupted = Run::sweep_interrupted(&context.get_db_connection())
+        .await?;
+
+    if interrupted > 0 {
+        log::warn!("Marked {interrupted} runs interrupted by a restart as errored.");
+    }
+
+    let spec = openapi_spec(context.config().openapi_internal());
+    let bind_address = context.config().bind_address();
+    let http_workers = context.config().http_workers();
+
+    let mut server = HttpServer::new(move || {
         let context = context.clone();
+        let json_limit = context.config().json_limit();
 
+        // Compression is negotiated trough Accept-Encoding,
+        // responses stay untouched for clients not sending it.
         App::new()
+            .wrap(Compress::default())
             .app_data(Data::new(context))
+            .app_data(json_config(json_limit))
             .service(authentication_scope())
-    })
-        .bind(("0.0.0.0", 8081))?
+            .service(profiles_scope())
+            .service(runs_scope())
+            .service(status_route)
+            .service(analytics_route)
+            .service(create_account_route)
+            .service(upcoming_route)
+            .service(readiness_route)
+            .service(openapi_service(spec.clone()))
+    });
+
+    if let Some(http_workers) = http_workers {
+        server = server.workers(http_workers);
+    }
+
+    server
+        .bind(bind_address)?
         .run()
         .await?;
 
@@ -1,3 +1,6 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Utc};
 use scrypt::password_hash::rand_core::OsRng;
 use scrypt::password_hash::{Error as PasswordHashError, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
 use scrypt::Scrypt;
@@ -6,6 +9,9 @@ use sqlx::{query, query_as, Error as SqlxError, PgPool};
 use sqlx::prelude::FromRow;
 use thiserror::Error;
 
+use crate::utils::application::environment::ReddytConfig;
+use crate::utils::external::database::timed_query;
+
 
 /// Represents solely server side errors, any client
 /// error such as existing email, invalid password...
@@ -28,7 +34,126 @@ pub enum AccountCreationResult {
 	Created(Account),
 
 	/// The account already exists, checked against email.
-	AlreadyExists
+	AlreadyExists,
+
+	/// The password doesn't meet the [`PasswordPolicy`].
+	Rejected(PasswordRejection)
+}
+
+
+/// Why a password doesn't meet the [`PasswordPolicy`],
+/// the `Display` is meant to be shown to the client.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordRejection {
+	#[error("The password must be at least {0} characters long.")]
+	TooShort(usize),
+
+	#[error("The password must contain letters and at least a digit or symbol.")]
+	NotComplex
+}
+
+
+/// The requirements passwords must meet
+/// for an account to be created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordPolicy {
+	/// The minimum amount of characters.
+	min_length: usize
+}
+
+impl PasswordPolicy {
+	/// Creates the policy from `RYT_PASSWORD_MIN_LENGTH`.
+	pub fn from_config(config: &ReddytConfig) -> Self {
+		Self {
+			min_length: config.password_min_length()
+		}
+	}
+
+	/// Checks a password against the policy, it must be
+	/// at least `min_length` characters long and mix
+	/// letters with digits or symbols.
+	///
+	/// Invalid UTF-8 sequences count as symbols.
+	pub fn check(&self, password: &[u8]) -> Result<(), PasswordRejection> {
+		let password = String::from_utf8_lossy(password);
+
+		if password.chars().count() < self.min_length {
+			return Err(PasswordRejection::TooShort(self.min_length));
+		}
+
+		let has_letters = password.chars().any(char::is_alphabetic);
+		let has_others = password.chars().any(|character| !character.is_alphabetic());
+
+		if !has_letters || !has_others {
+			return Err(PasswordRejection::NotComplex);
+		}
+
+		Ok(())
+	}
+}
+
+
+/// The outcome of an authentication attempt.
+///
+/// Callers must respond to `Invalid` and `Locked` the
+/// same way, otherwise unauthenticated clients could
+/// tell which accounts exist.
+pub enum AuthenticationResult {
+	/// The credentials are correct.
+	Authenticated(Account),
+
+	/// The account doesn't exist or the password is wrong.
+	Invalid,
+
+	/// The account is locked after too many failed attempts.
+	Locked
+}
+
+
+/// After how many consecutive failed authentications an
+/// account is locked, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+	/// The failed attempts that lock the account.
+	threshold: i32,
+
+	/// For how long the account stays locked, in seconds.
+	window_secs: i64
+}
+
+impl LockoutPolicy {
+	/// Creates the policy from `RYT_LOCKOUT_THRESHOLD`
+	/// and `RYT_LOCKOUT_WINDOW`.
+	pub fn from_config(config: &ReddytConfig) -> Self {
+		Self {
+			threshold: config.lockout_threshold(),
+			window_secs: config.lockout_window().as_secs().try_into().unwrap_or(i64::MAX)
+		}
+	}
+}
+
+
+/// Verifies a password against the hash of a random one,
+/// so rejecting a missing or locked account takes as long
+/// as rejecting a wrong password, otherwise the response
+/// time would tell which accounts exist.
+fn verify_dummy_password(password: &[u8]) -> Result<(), AccountError> {
+	static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+	let dummy_hash = match DUMMY_HASH.get() {
+		Some(dummy_hash) => dummy_hash,
+		None => {
+			let salt = SaltString::generate(&mut OsRng);
+			let dummy_hash = Scrypt.hash_password(&rand::random::<[u8; 32]>(), &salt)?;
+
+			DUMMY_HASH.get_or_init(|| dummy_hash.to_string())
+		}
+	};
+
+	// The outcome is irrelevant, only the time it takes.
+	let _ = Scrypt.verify_password(password, &PasswordHash::new(dummy_hash)?);
+
+	Ok(())
 }
 
 
@@ -56,6 +181,14 @@ pub struct Account {
 
 	/// The account password.
 	password: String,
+
+	/// Consecutive failed authentications, reset on
+	/// success and when the account gets locked.
+	failed_attempts: i32,
+
+	/// Until when authentication is rejected,
+	/// None if the account is not locked.
+	locked_until: Option<DateTime<Utc>>
 }
 
 
@@ -63,24 +196,37 @@ impl Account {
 	/// Creates an account using the provided account creadentials,
 	/// an [`AccountCreationResult`] is returned wrapping any
 	/// client errors or the account itself.
+	///
+	/// An existing account with the same email is left
+	/// untouched and reported as `AlreadyExists`, passwords
+	/// not meeting the policy are checked before hashing
+	/// and reported as `Rejected`.
 	pub async fn create_account(
 		connection: &PgPool,
-		credentials: AccountCredentials
+		credentials: AccountCredentials,
+		policy: &PasswordPolicy
 	) -> Result<AccountCreationResult, AccountError> {
 		match credentials {
 			AccountCredentials::Basic { email, password } => {
+				if let Err(rejection) = policy.check(&password) {
+					return Ok(AccountCreationResult::Rejected(rejection));
+				}
+
 				let salt = SaltString::generate(&mut OsRng);
 				let password_hash = Scrypt.hash_password(&password, &salt)?;
 
-				let user = query_as(r"
-					INSERT INTO accounts(email, password, salt)
-					VALUES ($1, $2, $3)
-					RETURNING *
-				")
-					.bind(email)
-					.bind(password_hash.to_string())
-					.bind(salt.as_str())
-					.fetch_optional(connection)
+				let user = timed_query(
+					"accounts.create_account",
+					query_as(r"
+						INSERT INTO accounts(email, password)
+						VALUES ($1, $2)
+						ON CONFLICT (email) DO NOTHING
+						RETURNING *
+					")
+						.bind(email)
+						.bind(password_hash.to_string())
+						.fetch_optional(connection)
+				)
 					.await?;
 
 				match user {
@@ -92,49 +238,195 @@ impl Account {
 	}
 
 	/// Attempt to authenticate to an account using
-	/// the provided credentials, if the credentials
-	/// are incorrect Ok(None) is returned, if there
-	/// is a server side error Err(..) is returned,
-	/// otherwise Ok(Some(Self)).
+	/// the provided credentials, if there is a server
+	/// side error Err(..) is returned, otherwise the
+	/// [`AuthenticationResult`].
+	///
+	/// Wrong passwords count towards the lockout policy,
+	/// once the threshold is hit the account is locked
+	/// for the policy window, a correct password resets
+	/// the count.
+	///
+	/// A password is hashed even if the account doesn't
+	/// exist or is locked, so every outcome takes as long.
 	pub async fn get_by_auth(
 		connection: &PgPool,
-		credentials: AccountCredentials
-	) -> Result<Option<Self>, AccountError> {
+		credentials: AccountCredentials,
+		lockout: &LockoutPolicy
+	) -> Result<AuthenticationResult, AccountError> {
 		match credentials {
 			AccountCredentials::Basic { email, password } => {
-				let account: Option<Self> = query_as(r"
-					SELECT * FROM accounts
-					WHERE email = $1
-					LIMIT 1
-				")
-					.bind(email)
-					.fetch_optional(connection)
+				let account: Option<Self> = timed_query(
+					"accounts.get_by_auth",
+					query_as(r"
+						SELECT * FROM accounts
+						WHERE email = $1
+						LIMIT 1
+					")
+						.bind(email)
+						.fetch_optional(connection)
+				)
 					.await?;
 
-				let Some(account) = account else {
-					return Ok(None);
+				let Some(mut account) = account else {
+					verify_dummy_password(&password)?;
+					return Ok(AuthenticationResult::Invalid);
 				};
 
+				if account.is_locked() {
+					verify_dummy_password(&password)?;
+					return Ok(AuthenticationResult::Locked);
+				}
+
 				let password_hash = PasswordHash::new(account.password_hash())?;
 				if Scrypt.verify_password(&password, &password_hash).is_ok() {
-					Ok(Some(account))
-				} else {
-					Ok(None)
+					if account.failed_attempts > 0 {
+						timed_query(
+							"accounts.get_by_auth.reset_attempts",
+							query(r"
+								UPDATE accounts
+								SET failed_attempts = 0, locked_until = NULL
+								WHERE id = $1
+							")
+								.bind(account.id)
+								.execute(connection)
+						)
+							.await?;
+
+						account.failed_attempts = 0;
+						account.locked_until = None;
+					}
+
+					return Ok(AuthenticationResult::Authenticated(account));
+				}
+
+				// Hitting the threshold locks the account and
+				// resets the count for when the lock expires.
+				let (locked_until,): (Option<DateTime<Utc>>,) = timed_query(
+					"accounts.get_by_auth.record_failure",
+					query_as(r"
+						UPDATE accounts
+						SET
+							failed_attempts = CASE
+								WHEN failed_attempts + 1 >= $2 THEN 0
+								ELSE failed_attempts + 1
+							END,
+							locked_until = CASE
+								WHEN failed_attempts + 1 >= $2
+									THEN NOW() + $3 * INTERVAL '1 second'
+								ELSE locked_until
+							END
+						WHERE id = $1
+						RETURNING locked_until
+					")
+						.bind(account.id)
+						.bind(lockout.threshold)
+						.bind(lockout.window_secs)
+						.fetch_one(connection)
+				)
+					.await?;
+
+				if locked_until.is_some_and(|locked_until| locked_until > Utc::now()) {
+					log::warn!("Locked the account {} after repeated failed authentications.", account.email);
+					return Ok(AuthenticationResult::Locked);
 				}
+
+				Ok(AuthenticationResult::Invalid)
 			}
 		}
 	}
 
 
+	/// Obtains an account by its email, None
+	/// if no account has that email.
+	pub async fn get_by_email(connection: &PgPool, email: &str) -> Result<Option<Self>, AccountError> {
+		Ok(
+			timed_query(
+				"accounts.get_by_email",
+				query_as(r"
+					SELECT * FROM accounts
+					WHERE email = $1
+				")
+					.bind(email)
+					.fetch_optional(connection)
+			)
+				.await?
+		)
+	}
+
+
+	/// Makes sure the admin configured by `RYT_ADMIN_EMAIL` and
+	/// `RYT_ADMIN_PASSWORD` exists as an account, creating it
+	/// or updating its password hash if the configured
+	/// password changed.
+	///
+	/// If the stored hash already matches the configured
+	/// password the account is returned without rehashing.
+	pub async fn upsert_from_config(
+		connection: &PgPool,
+		config: &ReddytConfig
+	) -> Result<Self, AccountError> {
+		let email = config.admin_email();
+		let password = config.admin_password().expose().as_bytes();
+
+		let existing: Option<Self> = timed_query(
+			"accounts.upsert_from_config.select",
+			query_as(r"
+				SELECT * FROM accounts
+				WHERE email = $1
+			")
+				.bind(email)
+				.fetch_optional(connection)
+		)
+			.await?;
+
+		if let Some(account) = &existing
+			&& Scrypt
+				.verify_password(password, &PasswordHash::new(account.password_hash())?)
+				.is_ok()
+		{
+			return Ok(account.clone());
+		}
+
+		let salt = SaltString::generate(&mut OsRng);
+		let password_hash = Scrypt.hash_password(password, &salt)?;
+
+		let account = timed_query(
+			"accounts.upsert_from_config.upsert",
+			query_as(r"
+				INSERT INTO accounts(email, password)
+				VALUES ($1, $2)
+				ON CONFLICT (email) DO UPDATE SET password = EXCLUDED.password
+				RETURNING *
+			")
+				.bind(email)
+				.bind(password_hash.to_string())
+				.fetch_one(connection)
+		)
+			.await?;
+
+		match existing {
+			Some(_) => log::info!("Updated the password of the admin account {email}."),
+			None => log::info!("Created the admin account {email}.")
+		}
+
+		Ok(account)
+	}
+
+
 	/// Delete an account from a pre-selected model.
+	#[allow(dead_code)]
 	pub async fn delete(self, connection: &PgPool) -> Result<(), AccountError> {
-		query(r"
-			DELETE FROM accounts
-			WHERE id = $1
-			LIMIT 1
-		")
-			.bind(self.id)
-			.execute(connection)
+		timed_query(
+			"accounts.delete",
+			query(r"
+				DELETE FROM accounts
+				WHERE id = $1
+				LIMIT 1
+			")
+				.bind(self.id)
+				.execute(connection)
+		)
 			.await?;
 
 		Ok(())
@@ -159,4 +451,44 @@ impl Account {
     pub fn password_hash(&self) -> &str {
         &self.password
     }
+
+	/// Whether authentication is currently rejected.
+	#[inline]
+    pub fn is_locked(&self) -> bool {
+        self.locked_until
+            .is_some_and(|locked_until| locked_until > Utc::now())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const POLICY: PasswordPolicy = PasswordPolicy { min_length: 12 };
+
+	#[test]
+	fn short_passwords_are_rejected() {
+		assert_eq!(POLICY.check(b"short-1"), Err(PasswordRejection::TooShort(12)));
+	}
+
+	#[test]
+	fn letter_only_passwords_are_rejected() {
+		assert_eq!(POLICY.check(b"onlylettershere"), Err(PasswordRejection::NotComplex));
+	}
+
+	#[test]
+	fn digit_only_passwords_are_rejected() {
+		assert_eq!(POLICY.check(b"123456789012"), Err(PasswordRejection::NotComplex));
+	}
+
+	#[test]
+	fn length_counts_characters_not_bytes() {
+		assert_eq!(POLICY.check("ñññññ-1".as_bytes()), Err(PasswordRejection::TooShort(12)));
+	}
+
+	#[test]
+	fn compliant_passwords_pass() {
+		assert_eq!(POLICY.check(b"correct-horse-1"), Ok(()));
+	}
 }
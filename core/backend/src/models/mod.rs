@@ -2,6 +2,8 @@
 pub mod accounts;
 pub mod profile_stage_layers;
 pub mod profile_stages;
+pub mod profiles;
+pub mod run_usage;
 pub mod runs;
 pub mod upload_platforms;
 pub mod uploads;
@@ -4,6 +4,7 @@ use sqlx::prelude::FromRow;
 
 /// Model representation for profile stage layer database schema.
 #[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, PartialOrd, Clone)]
+#[allow(dead_code)]
 pub struct ProfileStageLayer {
 	/// The primary key for this model.
 	id: i32,
@@ -18,6 +19,7 @@ pub struct ProfileStageLayer {
 	layer_data: Vec<u8>
 }
 
+#[allow(dead_code)]
 impl ProfileStageLayer {
 	/// The primary key for this model.
     pub fn id(&self) -> i32 {
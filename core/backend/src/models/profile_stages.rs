@@ -1,9 +1,22 @@
 use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, Error as SqlxError, PgPool};
 use sqlx::prelude::FromRow;
+use thiserror::Error;
+use utoipa::ToSchema;
 
+use crate::utils::external::database::timed_query;
+
+
+/// Represents solely server side errors related
+/// to profile stage operations.
+#[derive(Debug, Error)]
+pub enum ProfileStageError {
+	#[error("Error while querying the database, {0:#}")]
+	DatabaseConnection(#[from] SqlxError)
+}
 
 /// Model representation for profile stage database schema.
-#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, PartialOrd, Clone)]
+#[derive(Serialize, Deserialize, FromRow, ToSchema, Debug, PartialEq, PartialOrd, Clone)]
 pub struct ProfileStage {
 	/// The primary key for this model.
 	id: i32,
@@ -16,28 +29,225 @@ pub struct ProfileStage {
 
 	/// The FIFO stage connection order, if -1 is the first stage,
 	/// otherwise the last stage ID. If null the node is disconnected.
-	last_stage: Option<i32>
+	last_stage: Option<i32>,
+
+	/// Whether this stage is composed, a disabled stage
+	/// is skipped but keeps the chain linked through it.
+	enabled: bool
 }
 
 impl ProfileStage {
+	/// Obtains a stage by its primary key, only
+	/// if it belongs to the given profile.
+	pub async fn get_by_id(
+		connection: &PgPool,
+		profile_id: i32,
+		id: i32
+	) -> Result<Option<Self>, ProfileStageError> {
+		let stage = timed_query(
+			"profile_stages.get_by_id",
+			query_as(r"
+				SELECT * FROM profile_stages
+				WHERE id = $1 AND profile_id = $2
+			")
+				.bind(id)
+				.bind(profile_id)
+				.fetch_optional(connection)
+		)
+			.await?;
+
+		Ok(stage)
+	}
+
+	/// The enabled stages of a profile in composition order,
+	/// see `ordered` for how the chain is walked.
+	///
+	/// This is what the compositor iterates, it has
+	/// no caller until a compositor exists.
+	#[allow(dead_code)]
+	pub async fn ordered_for_profile(
+		connection: &PgPool,
+		profile_id: i32
+	) -> Result<Vec<Self>, ProfileStageError> {
+		let stages = timed_query(
+			"profile_stages.ordered_for_profile",
+			query_as(r"
+				SELECT * FROM profile_stages
+				WHERE profile_id = $1
+				ORDER BY id
+			")
+				.bind(profile_id)
+				.fetch_all(connection)
+		)
+			.await?;
+
+		Ok(ordered(stages))
+	}
+
+	/// Enables or disables this stage, the stage
+	/// connections are left untouched.
+	pub async fn set_enabled(
+		&mut self,
+		connection: &PgPool,
+		enabled: bool
+	) -> Result<(), ProfileStageError> {
+		timed_query(
+			"profile_stages.set_enabled",
+			query(r"
+				UPDATE profile_stages
+				SET enabled = $2
+				WHERE id = $1
+			")
+				.bind(self.id)
+				.bind(enabled)
+				.execute(connection)
+		)
+			.await?;
+
+		self.enabled = enabled;
+
+		Ok(())
+	}
+
 	/// The primary key for this model.
+    #[allow(dead_code)]
     pub fn id(&self) -> i32 {
         self.id
     }
 
 	/// The profile this video stage belongs to.
+    #[allow(dead_code)]
     pub fn profile_id(&self) -> i32 {
         self.profile_id
     }
 
 	/// The name of this stage to be referenced by other stages.
+    #[allow(dead_code)]
     pub fn name(&self) -> &str {
         &self.name
     }
 
 	/// The FIFO stage connection order, if -1 is the first stage,
 	/// otherwise the last stage ID. If null the node is disconnected.
+    #[allow(dead_code)]
     pub fn last_stage(&self) -> Option<i32> {
         self.last_stage
     }
+
+	/// Whether this stage is composed, a disabled stage
+	/// is skipped but keeps the chain linked through it.
+    #[allow(dead_code)]
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// Walks the stage chain from the stage connected to -1,
+/// each next stage being the one connected to the previous.
+///
+/// Disabled stages are walked trough but not returned, and
+/// disconnected stages are never reached. Every stage is
+/// visited at most once, so a cyclic chain ends.
+fn ordered(mut stages: Vec<ProfileStage>) -> Vec<ProfileStage> {
+	let mut chain = Vec::with_capacity(stages.len());
+	let mut previous = -1;
+
+	while let Some(index) = stages.iter().position(|stage| stage.last_stage == Some(previous)) {
+		let stage = stages.remove(index);
+		previous = stage.id;
+
+		if stage.enabled {
+			chain.push(stage);
+		}
+	}
+
+	chain
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::testing::{insert_profile, test_pool};
+
+	fn stage(id: i32, last_stage: Option<i32>, enabled: bool) -> ProfileStage {
+		ProfileStage {
+			id,
+			profile_id: 1,
+			name: format!("stage-{id}"),
+			last_stage,
+			enabled
+		}
+	}
+
+	fn ids(stages: Vec<ProfileStage>) -> Vec<i32> {
+		stages.into_iter().map(|stage| stage.id).collect()
+	}
+
+	#[test]
+	fn stages_follow_the_chain() {
+		let stages = vec![
+			stage(3, Some(1), true),
+			stage(1, Some(-1), true),
+			stage(2, Some(3), true),
+			stage(4, None, true)
+		];
+
+		assert_eq!(ids(ordered(stages)), [1, 3, 2]);
+	}
+
+	#[test]
+	fn a_disabled_middle_stage_is_skipped_but_its_successor_runs() {
+		let stages = vec![
+			stage(1, Some(-1), true),
+			stage(2, Some(1), false),
+			stage(3, Some(2), true)
+		];
+
+		assert_eq!(ids(ordered(stages)), [1, 3]);
+	}
+
+	#[test]
+	fn cyclic_chains_end() {
+		let stages = vec![
+			stage(1, Some(-1), true),
+			stage(2, Some(3), true),
+			stage(3, Some(2), true)
+		];
+
+		assert_eq!(ids(ordered(stages)), [1]);
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn disabled_stages_are_persisted_and_skipped() {
+		let connection = test_pool().await;
+		let profile = insert_profile(&connection, "0 * * * *").await;
+		let first = rand::random_range(1..i32::MAX - 2);
+
+		for (id, last_stage) in [(first, -1), (first + 1, first), (first + 2, first + 1)] {
+			query("INSERT INTO profile_stages(id, profile_id, name, last_stage) VALUES ($1, $2, $3, $4)")
+				.bind(id)
+				.bind(profile.id())
+				.bind(format!("stage-{id}"))
+				.bind(last_stage)
+				.execute(&connection)
+				.await
+				.expect("Couldn't insert the test stage");
+		}
+
+		let mut middle = ProfileStage::get_by_id(&connection, profile.id(), first + 1)
+			.await
+			.expect("The stage can be queried")
+			.expect("The stage exists");
+
+		middle.set_enabled(&connection, false)
+			.await
+			.expect("The stage can be disabled");
+
+		let stages = ProfileStage::ordered_for_profile(&connection, profile.id())
+			.await
+			.expect("The stages can be queried");
+
+		assert_eq!(ids(stages), [first, first + 2]);
+	}
 }
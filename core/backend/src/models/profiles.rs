@@ -0,0 +1,1055 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+use croner::errors::CronError;
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+use sqlx::{query_as, Error as SqlxError, PgPool};
+use sqlx::prelude::{FromRow, Type};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::models::runs::{RunErrorKind, RunErrorSource};
+use crate::utils::external::database::timed_query;
+
+
+/// Represents solely server side errors related
+/// to profile operations.
+#[derive(Debug, Error)]
+pub enum ProfileError {
+	#[error("Error while querying the database, {0:#}")]
+	DatabaseConnection(#[from] SqlxError),
+
+	#[error("The profile schedule is not a valid cron expression, {0:#}")]
+	InvalidSchedule(#[from] CronError),
+
+	#[error("The profile schedule is valid but never fires.")]
+	ScheduleNeverFires,
+
+	#[error("The profile was modified since it was read.")]
+	VersionConflict,
+
+	#[error("The profile timezone \"{0}\" is not a valid IANA timezone.")]
+	InvalidTimezone(String),
+
+	#[error("The profile daily run cap can't be negative.")]
+	NegativeDailyRunCap,
+
+	#[error("The profile schedule jitter window can't be negative.")]
+	NegativeScheduleJitter,
+
+	#[error("The profile locale \"{0}\" is not a valid BCP-47 language tag.")]
+	InvalidLocale(String)
+}
+
+
+impl RunErrorSource for ProfileError {
+	fn kind(&self) -> RunErrorKind {
+		match self {
+			Self::DatabaseConnection(_) => RunErrorKind::Transient,
+			Self::InvalidSchedule(_)
+				| Self::ScheduleNeverFires
+				| Self::InvalidTimezone(_)
+				| Self::NegativeDailyRunCap
+				| Self::NegativeScheduleJitter
+				| Self::InvalidLocale(_) => RunErrorKind::Configuration,
+			Self::VersionConflict => RunErrorKind::Transient
+		}
+	}
+}
+
+
+/// The output containers the compositor can produce.
+#[derive(Serialize, Deserialize, Type, ToSchema, Debug, PartialEq, PartialOrd, Clone, Copy)]
+#[sqlx(type_name = "video_container", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VideoContainer {
+	/// H.264 video with AAC audio.
+	Mp4,
+
+	/// VP9 video with Opus audio.
+	Webm
+}
+
+/// Aggregated run statistics for a single profile.
+///
+/// A profile without runs has every count at zero
+/// and every other field as None.
+#[derive(Serialize, FromRow, ToSchema, Debug, Clone)]
+pub struct ProfileStats {
+	/// Every run the profile started, in any state.
+	total_runs: i64,
+
+	/// The runs that finished without error.
+	successful_runs: i64,
+
+	/// The successful runs over the runs that finished
+	/// or errored, None if none did.
+	success_rate: Option<f64>,
+
+	/// The average time finished runs took, in seconds.
+	average_duration_secs: Option<f64>,
+
+	/// When did the latest successful run finish.
+	last_success_at: Option<DateTime<Utc>>
+}
+
+/// The runs the capped profiles started today,
+/// obtained with `Profile::runs_today`.
+#[derive(Debug, Default)]
+pub struct RunsToday(HashMap<i32, i64>);
+
+impl RunsToday {
+	/// The runs `profile` may still start today,
+	/// None if the profile has no daily run cap.
+	pub fn remaining(&self, profile: &Profile) -> Option<i64> {
+		let cap = profile.daily_run_cap?;
+		let started = self.0.get(&profile.id).copied().unwrap_or(0);
+
+		Some((i64::from(cap) - started).max(0))
+	}
+}
+
+/// Model representation for profiles database schema.
+#[derive(Serialize, Deserialize, FromRow, ToSchema, Debug, PartialEq, PartialOrd, Clone)]
+pub struct Profile {
+	/// The primary key for this model.
+	id: i32,
+
+	/// The account that owns this profile.
+	account_id: i32,
+
+	/// The profile human readable identifier.
+	name: String,
+
+	/// A human readable description for the profile.
+	description: Option<String>,
+
+	/// A cron schedule to define when a video should
+	/// be generated and uploaded.
+	schedule: String,
+
+	/// The IANA timezone the schedule is evaluated in.
+	timezone: String,
+
+	/// The BCP-47 language tag generated text
+	/// and narration are in.
+	locale: String,
+
+	/// Whether the schedule is paused and no videos
+	/// should be generated.
+	paused: bool,
+
+	/// Whether the schedule is a valid cron expression
+	/// that never fires, set by `Profile::update`.
+	schedule_warning: bool,
+
+	/// Profiles with a higher priority run first
+	/// when they are due at the same time.
+	priority: i32,
+
+	/// The maximum delay in seconds applied to every
+	/// scheduled run, see `Profile::schedule_jitter`.
+	schedule_jitter_secs: i32,
+
+	/// The maximum amount of non errored runs per day
+	/// in the profile timezone, if None there is no cap.
+	daily_run_cap: Option<i32>,
+
+	/// The aspect ratio height for the video.
+	ar_height: i32,
+
+	/// The aspect ratio width for the video.
+	ar_width: i32,
+
+	/// The rendered video width in pixels.
+	output_width: i32,
+
+	/// The rendered video height in pixels.
+	output_height: i32,
+
+	/// The rendered video framerate.
+	output_fps: i32,
+
+	/// The rendered video container format.
+	container: VideoContainer,
+
+	/// The upload title, may contain `{{variable}}`
+	/// placeholders, see `render_template`.
+	upload_title_template: String,
+
+	/// The upload description, may contain `{{variable}}`
+	/// placeholders, see `render_template`.
+	upload_description_template: String,
+
+	/// The upload tags, each may contain `{{variable}}`
+	/// placeholders, see `render_template`.
+	upload_tags_template: Vec<String>,
+
+	/// When was this profile last modified, every
+	/// mutating query must bump this.
+	updated_at: DateTime<Utc>,
+
+	/// The optimistic locking version, updates must be
+	/// conditioned to it and increment it.
+	version: i32
+}
+
+/// The editable settings of a profile, together
+/// with the version they were read at.
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+pub struct ProfileUpdate {
+	/// The profile human readable identifier.
+	name: String,
+
+	/// A human readable description for the profile.
+	description: Option<String>,
+
+	/// A cron schedule to define when a video should
+	/// be generated and uploaded.
+	schedule: String,
+
+	/// The IANA timezone the schedule is evaluated in.
+	timezone: String,
+
+	/// The BCP-47 language tag generated text
+	/// and narration are in.
+	locale: String,
+
+	/// Whether the schedule is paused.
+	paused: bool,
+
+	/// The maximum amount of non errored runs per day.
+	daily_run_cap: Option<i32>,
+
+	/// Profiles with a higher priority run first.
+	priority: i32,
+
+	/// The maximum delay in seconds applied to every run.
+	schedule_jitter_secs: i32,
+
+	/// The version of the profile the client read.
+	version: i32
+}
+
+impl Profile {
+	/// Counts all the profiles and how many of them
+	/// are paused, returned as `(total, paused)`.
+	pub async fn count(connection: &PgPool) -> Result<(i64, i64), ProfileError> {
+		let counts = timed_query(
+			"profiles.count",
+			query_as(r"
+				SELECT COUNT(*), COUNT(*) FILTER (WHERE paused)
+				FROM profiles
+			")
+				.fetch_one(connection)
+		)
+			.await?;
+
+		Ok(counts)
+	}
+
+	/// Obtains a profile by its primary key,
+	/// Ok(None) is returned if it doesn't exist.
+	pub async fn get_by_id(connection: &PgPool, id: i32) -> Result<Option<Self>, ProfileError> {
+		let profile = timed_query(
+			"profiles.get_by_id",
+			query_as(r"
+				SELECT * FROM profiles
+				WHERE id = $1
+			")
+				.bind(id)
+				.fetch_optional(connection)
+		)
+			.await?;
+
+		Ok(profile)
+	}
+
+	/// Obtains every profile whose schedule is not paused,
+	/// the highest priority first.
+	pub async fn list_unpaused(connection: &PgPool) -> Result<Vec<Self>, ProfileError> {
+		let profiles = timed_query(
+			"profiles.list_unpaused",
+			query_as(r"
+				SELECT * FROM profiles
+				WHERE NOT paused
+				ORDER BY priority DESC, id
+			")
+				.fetch_all(connection)
+		)
+			.await?;
+
+		Ok(profiles)
+	}
+
+	/// Replaces the editable settings of this profile, only
+	/// if it wasn't modified since `update.version` was read,
+	/// otherwise `VersionConflict` is returned and nothing
+	/// is written.
+	///
+	/// On success the version is incremented and this
+	/// profile is replaced with the stored row.
+	///
+	/// The settings are validated before writing, so a
+	/// stored profile always has a valid timezone and
+	/// locale, a schedule that parses and a non negative
+	/// cap.
+	///
+	/// The schedule warning is set when the schedule
+	/// never fires and cleared otherwise, the profile
+	/// is not paused since it may still be run manually.
+	pub async fn update(
+		&mut self,
+		connection: &PgPool,
+		update: ProfileUpdate
+	) -> Result<(), ProfileError> {
+		let timezone = parse_timezone(&update.timezone)?;
+		validate_locale(&update.locale)?;
+
+		if update.daily_run_cap.is_some_and(|cap| cap < 0) {
+			return Err(ProfileError::NegativeDailyRunCap);
+		}
+
+		if update.schedule_jitter_secs < 0 {
+			return Err(ProfileError::NegativeScheduleJitter);
+		}
+
+		// Searching the next occurrence also parses the schedule,
+		// a schedule that never fires is still stored, flagged.
+		let schedule_warning = match next_occurrence(&update.schedule, timezone, Utc::now()) {
+			Ok(_) => false,
+			Err(ProfileError::ScheduleNeverFires) => true,
+			Err(error) => return Err(error)
+		};
+
+		let updated: Option<Self> = timed_query(
+			"profiles.update",
+			query_as(r"
+				UPDATE profiles
+				SET
+					name = $3,
+					description = $4,
+					schedule = $5,
+					timezone = $6,
+					locale = $7,
+					paused = $8,
+					daily_run_cap = $9,
+					priority = $10,
+					schedule_jitter_secs = $11,
+					schedule_warning = $12,
+					version = version + 1,
+					updated_at = NOW()
+				WHERE id = $1 AND version = $2
+				RETURNING *
+			")
+				.bind(self.id)
+				.bind(update.version)
+				.bind(update.name)
+				.bind(update.description)
+				.bind(update.schedule)
+				.bind(update.timezone)
+				.bind(update.locale)
+				.bind(update.paused)
+				.bind(update.daily_run_cap)
+				.bind(update.priority)
+				.bind(update.schedule_jitter_secs)
+				.bind(schedule_warning)
+				.fetch_optional(connection)
+		)
+			.await?;
+
+		*self = updated.ok_or(ProfileError::VersionConflict)?;
+
+		Ok(())
+	}
+
+	/// The next time the schedule fires strictly after
+	/// `after`, the cron expression is evaluated in the
+	/// profile timezone and offset by `schedule_jitter`.
+	pub fn next_run(&self, after: DateTime<Utc>) -> Result<DateTime<Utc>, ProfileError> {
+		let jitter = self.schedule_jitter();
+
+		// The occurrence is searched before `after` by the
+		// jitter, so a jittered run that is still pending
+		// isn't skipped in favour of the next occurrence.
+		let next_run = next_occurrence(&self.schedule, self.timezone()?, after - jitter)?;
+
+		Ok(next_run + jitter)
+	}
+
+	/// The offset applied to every occurrence of the schedule,
+	/// between zero and `schedule_jitter_secs` inclusive.
+	///
+	/// The offset is derived from the profile id, so it's
+	/// stable for a profile while profiles sharing a cron
+	/// expression are spread across the window.
+	pub fn schedule_jitter(&self) -> TimeDelta {
+		jitter_offset(self.id, self.schedule_jitter_secs)
+	}
+
+	/// Aggregates the run history of this profile, see
+	/// [`ProfileStats`] for what is computed.
+	///
+	/// Runs that are still in progress only count
+	/// towards the total.
+	pub async fn stats(&self, connection: &PgPool) -> Result<ProfileStats, ProfileError> {
+		let stats = timed_query(
+			"profiles.stats",
+			query_as(r"
+				SELECT
+					COUNT(*) AS total_runs,
+					COUNT(*) FILTER (
+						WHERE finished_at IS NOT NULL AND error IS NULL
+					) AS successful_runs,
+					COUNT(*) FILTER (
+						WHERE finished_at IS NOT NULL AND error IS NULL
+					)::FLOAT8 / NULLIF(COUNT(*) FILTER (
+						WHERE finished_at IS NOT NULL OR error IS NOT NULL
+					), 0) AS success_rate,
+					(AVG(EXTRACT(EPOCH FROM finished_at - started_at)) FILTER (
+						WHERE finished_at IS NOT NULL
+					))::FLOAT8 AS average_duration_secs,
+					MAX(finished_at) FILTER (
+						WHERE error IS NULL
+					) AS last_success_at
+				FROM runs
+				WHERE profile_id = $1
+			")
+				.bind(self.id)
+				.fetch_one(connection)
+		)
+			.await?;
+
+		Ok(stats)
+	}
+
+	/// Counts the runs every capped profile in `profiles`
+	/// started today, finished and in-progress runs count
+	/// but errored ones don't.
+	///
+	/// Each day starts at the profile timezone midnight,
+	/// see `day_start`. Uncapped profiles and profiles
+	/// with an invalid timezone are left out, and all the
+	/// counts are obtained with a single query.
+	pub async fn runs_today(
+		connection: &PgPool,
+		profiles: &[Self],
+		now: DateTime<Utc>
+	) -> Result<RunsToday, ProfileError> {
+		let (profile_ids, day_starts): (Vec<i32>, Vec<DateTime<Utc>>) = profiles
+			.iter()
+			.filter(|profile| profile.daily_run_cap.is_some())
+			.filter_map(|profile| {
+				let timezone = profile.timezone().ok()?;
+				Some((profile.id, day_start(timezone, now.with_timezone(&timezone).date_naive())))
+			})
+			.unzip();
+
+		if profile_ids.is_empty() {
+			return Ok(RunsToday::default());
+		}
+
+		let counts: Vec<(i32, i64)> = timed_query(
+			"profiles.runs_today",
+			query_as(r"
+				SELECT capped.profile_id, COUNT(runs.id)
+				FROM UNNEST($1::INT[], $2::TIMESTAMPTZ[]) AS capped(profile_id, day_start)
+				LEFT JOIN runs
+					ON runs.profile_id = capped.profile_id
+					AND runs.started_at >= capped.day_start
+					AND runs.error IS NULL
+				GROUP BY capped.profile_id
+			")
+				.bind(profile_ids)
+				.bind(day_starts)
+				.fetch_all(connection)
+		)
+			.await?;
+
+		Ok(RunsToday(counts.into_iter().collect()))
+	}
+
+	/// Whether the daily run cap excludes a run due at
+	/// `runs_at`, `runs_today` being counted at `now`.
+	///
+	/// The cap resets at the profile midnight, so only
+	/// runs due the same local day as `now` are excluded.
+	pub fn cap_excludes(
+		&self,
+		runs_at: DateTime<Utc>,
+		now: DateTime<Utc>,
+		runs_today: &RunsToday
+	) -> Result<bool, ProfileError> {
+		let timezone = self.timezone()?;
+		let due_today = runs_at.with_timezone(&timezone).date_naive()
+			== now.with_timezone(&timezone).date_naive();
+
+		Ok(due_today && runs_today.remaining(self) == Some(0))
+	}
+
+
+	/// The primary key for this model.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+	/// The account that owns this profile.
+    #[allow(dead_code)]
+    pub fn account_id(&self) -> i32 {
+        self.account_id
+    }
+
+	/// The profile human readable identifier.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+	/// A human readable description for the profile.
+    #[allow(dead_code)]
+    pub fn description(&self) -> Option<&String> {
+        self.description.as_ref()
+    }
+
+	/// A cron schedule to define when a video should
+	/// be generated and uploaded.
+    #[allow(dead_code)]
+    pub fn schedule(&self) -> &str {
+        &self.schedule
+    }
+
+	/// The timezone the schedule is evaluated in, a
+	/// stored name that is not a valid IANA timezone
+	/// is rejected rather than assumed to be UTC.
+    pub fn timezone(&self) -> Result<Tz, ProfileError> {
+        parse_timezone(&self.timezone)
+    }
+
+	/// The BCP-47 language tag generated text
+	/// and narration are in.
+    #[allow(dead_code)]
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+	/// Whether the schedule is paused and no videos
+	/// should be generated.
+    #[allow(dead_code)]
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+	/// Whether the schedule is a valid cron
+	/// expression that never fires.
+    #[allow(dead_code)]
+    pub fn schedule_warning(&self) -> bool {
+        self.schedule_warning
+    }
+
+	/// Profiles with a higher priority run first
+	/// when they are due at the same time.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+	/// The maximum delay in seconds applied to every
+	/// scheduled run, zero disables the jitter.
+    #[allow(dead_code)]
+    pub fn schedule_jitter_secs(&self) -> i32 {
+        self.schedule_jitter_secs
+    }
+
+	/// The maximum amount of non errored runs per day
+	/// in the profile timezone, if None there is no cap.
+    #[allow(dead_code)]
+    pub fn daily_run_cap(&self) -> Option<i32> {
+        self.daily_run_cap
+    }
+
+	/// The aspect ratio height for the video.
+    #[allow(dead_code)]
+    pub fn ar_height(&self) -> i32 {
+        self.ar_height
+    }
+
+	/// The aspect ratio width for the video.
+    #[allow(dead_code)]
+    pub fn ar_width(&self) -> i32 {
+        self.ar_width
+    }
+
+	/// The rendered video width in pixels.
+    #[allow(dead_code)]
+    pub fn output_width(&self) -> i32 {
+        self.output_width
+    }
+
+	/// The rendered video height in pixels.
+    #[allow(dead_code)]
+    pub fn output_height(&self) -> i32 {
+        self.output_height
+    }
+
+	/// The rendered video framerate.
+    #[allow(dead_code)]
+    pub fn output_fps(&self) -> i32 {
+        self.output_fps
+    }
+
+	/// The rendered video container format.
+    #[allow(dead_code)]
+    pub fn container(&self) -> VideoContainer {
+        self.container
+    }
+
+	/// The upload title, may contain `{{variable}}`
+	/// placeholders, see `render_template`.
+    #[allow(dead_code)]
+    pub fn upload_title_template(&self) -> &str {
+        &self.upload_title_template
+    }
+
+	/// The upload description, may contain `{{variable}}`
+	/// placeholders, see `render_template`.
+    #[allow(dead_code)]
+    pub fn upload_description_template(&self) -> &str {
+        &self.upload_description_template
+    }
+
+	/// The upload tags, each may contain `{{variable}}`
+	/// placeholders, see `render_template`.
+    #[allow(dead_code)]
+    pub fn upload_tags_template(&self) -> &[String] {
+        &self.upload_tags_template
+    }
+
+	/// When was this profile last modified, every
+	/// mutating query must bump this.
+    #[allow(dead_code)]
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+	/// The optimistic locking version, updates must be
+	/// conditioned to it and increment it.
+    #[allow(dead_code)]
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+}
+
+/// Validates a BCP-47 language tag, as in `en-US`, every
+/// subtag must be registered and the tag must fit the
+/// 35 characters of the locale column.
+fn validate_locale(locale: &str) -> Result<(), ProfileError> {
+	let valid = locale.len() <= 35
+		&& LanguageTag::parse(locale).is_ok_and(|tag| tag.is_valid());
+
+	if valid {
+		Ok(())
+	} else {
+		Err(ProfileError::InvalidLocale(locale.to_string()))
+	}
+}
+
+/// Parses an IANA timezone name, as in `Europe/Madrid`.
+fn parse_timezone(timezone: &str) -> Result<Tz, ProfileError> {
+	timezone
+		.parse()
+		.map_err(|_| ProfileError::InvalidTimezone(timezone.to_string()))
+}
+
+/// The first occurrence of `schedule` strictly after
+/// `after`, evaluated in `timezone`.
+///
+/// croner gives up searching after a few years, which
+/// is how a schedule that never fires is detected.
+fn next_occurrence(
+	schedule: &str,
+	timezone: Tz,
+	after: DateTime<Utc>
+) -> Result<DateTime<Utc>, ProfileError> {
+	let occurrence = Cron::new(schedule)
+		.parse()?
+		.find_next_occurrence(&after.with_timezone(&timezone), false)
+		.map_err(|error| match error {
+			CronError::TimeSearchLimitExceeded => ProfileError::ScheduleNeverFires,
+			error => error.into()
+		})?;
+
+	Ok(occurrence.with_timezone(&Utc))
+}
+
+/// When `date` starts in `timezone`, its midnight or, on
+/// days a DST change skips midnight as in America/Santiago,
+/// the first local time after the gap.
+fn day_start(timezone: Tz, date: NaiveDate) -> DateTime<Utc> {
+	let midnight = date.and_time(NaiveTime::MIN);
+
+	// DST gaps last at most a couple of hours, the
+	// day is searched in quarter hour steps.
+	(0..24 * 4)
+		.map(|quarter| midnight + TimeDelta::minutes(15 * quarter))
+		.find_map(|local| local.and_local_timezone(timezone).earliest())
+		.map_or_else(|| midnight.and_utc(), |start| start.with_timezone(&Utc))
+}
+
+/// The offset for the profile `id` within a window of
+/// `window_secs` seconds, see `Profile::schedule_jitter`.
+fn jitter_offset(id: i32, window_secs: i32) -> TimeDelta {
+	let window = window_secs.max(0) as u64;
+
+	if window == 0 {
+		return TimeDelta::zero();
+	}
+
+	// The splitmix64 finalizer, consecutive ids
+	// produce unrelated offsets.
+	let mut seed = (id as u64).wrapping_add(0x9E3779B97F4A7C15);
+	seed = (seed ^ (seed >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+	seed = (seed ^ (seed >> 27)).wrapping_mul(0x94D049BB133111EB);
+	seed ^= seed >> 31;
+
+	TimeDelta::seconds((seed % (window + 1)) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::testing::{insert_profile, insert_run, test_pool};
+
+	/// An update keeping every setting of `profile`,
+	/// as sent by a client that read it.
+	fn unchanged(profile: &Profile) -> ProfileUpdate {
+		ProfileUpdate {
+			name: profile.name.clone(),
+			description: profile.description.clone(),
+			schedule: profile.schedule.clone(),
+			timezone: profile.timezone.clone(),
+			locale: profile.locale.clone(),
+			paused: profile.paused,
+			daily_run_cap: profile.daily_run_cap,
+			priority: profile.priority,
+			schedule_jitter_secs: profile.schedule_jitter_secs,
+			version: profile.version
+		}
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn only_the_first_of_two_stale_writers_succeeds() {
+		let connection = test_pool().await;
+		let profile = insert_profile(&connection, "0 * * * *").await;
+
+		let mut first = profile.clone();
+		let mut second = profile.clone();
+
+		first
+			.update(&connection, ProfileUpdate { paused: true, ..unchanged(&profile) })
+			.await
+			.expect("The first writer read the latest version");
+
+		assert_eq!(first.version(), profile.version() + 1);
+
+		let result = second
+			.update(&connection, ProfileUpdate { priority: 10, ..unchanged(&profile) })
+			.await;
+
+		assert!(matches!(result, Err(ProfileError::VersionConflict)));
+
+		let stored = Profile::get_by_id(&connection, profile.id())
+			.await
+			.unwrap()
+			.unwrap();
+
+		assert!(stored.paused());
+		assert_eq!(stored.priority(), profile.priority());
+	}
+
+	#[test]
+	fn valid_timezones_parse() {
+		assert_eq!(parse_timezone("Europe/Madrid").unwrap(), Tz::Europe__Madrid);
+		assert_eq!(parse_timezone("UTC").unwrap(), Tz::UTC);
+	}
+
+	#[test]
+	fn invalid_timezones_are_rejected() {
+		assert!(matches!(
+			parse_timezone("Mars/Olympus_Mons"),
+			Err(ProfileError::InvalidTimezone(timezone)) if timezone == "Mars/Olympus_Mons"
+		));
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn updates_with_an_invalid_timezone_are_rejected() {
+		let connection = test_pool().await;
+		let mut profile = insert_profile(&connection, "0 * * * *").await;
+		let update = ProfileUpdate { timezone: "Nowhere/Special".to_string(), ..unchanged(&profile) };
+
+		assert!(matches!(
+			profile.update(&connection, update).await,
+			Err(ProfileError::InvalidTimezone(_))
+		));
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn updates_with_an_invalid_schedule_are_rejected() {
+		let connection = test_pool().await;
+		let mut profile = insert_profile(&connection, "0 * * * *").await;
+		let update = ProfileUpdate { schedule: "every monday".to_string(), ..unchanged(&profile) };
+
+		assert!(matches!(
+			profile.update(&connection, update).await,
+			Err(ProfileError::InvalidSchedule(_))
+		));
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn updates_with_a_negative_cap_are_rejected() {
+		let connection = test_pool().await;
+		let mut profile = insert_profile(&connection, "0 * * * *").await;
+		let update = ProfileUpdate { daily_run_cap: Some(-1), ..unchanged(&profile) };
+
+		assert!(matches!(
+			profile.update(&connection, update).await,
+			Err(ProfileError::NegativeDailyRunCap)
+		));
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn updates_with_a_negative_jitter_are_rejected() {
+		let connection = test_pool().await;
+		let mut profile = insert_profile(&connection, "0 * * * *").await;
+		let update = ProfileUpdate { schedule_jitter_secs: -30, ..unchanged(&profile) };
+
+		assert!(matches!(
+			profile.update(&connection, update).await,
+			Err(ProfileError::NegativeScheduleJitter)
+		));
+	}
+
+	/// Inserts a profile with a daily run cap of one.
+	async fn insert_capped_profile(connection: &PgPool) -> Profile {
+		let profile = insert_profile(connection, "* * * * *").await;
+
+		query_as(r"
+			UPDATE profiles
+			SET daily_run_cap = 1
+			WHERE id = $1
+			RETURNING *
+		")
+			.bind(profile.id())
+			.fetch_one(connection)
+			.await
+			.unwrap()
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn profiles_at_their_cap_have_no_runs_left() {
+		let connection = test_pool().await;
+
+		let at_cap = insert_capped_profile(&connection).await;
+		let below_cap = insert_capped_profile(&connection).await;
+		let uncapped = insert_profile(&connection, "* * * * *").await;
+
+		insert_run(&connection, at_cap.id(), true, None).await;
+		// Errored runs don't count towards the cap.
+		insert_run(&connection, below_cap.id(), true, Some("failed")).await;
+
+		let profiles = [at_cap.clone(), below_cap.clone(), uncapped.clone()];
+		let runs_today = Profile::runs_today(&connection, &profiles, Utc::now())
+			.await
+			.unwrap();
+
+		assert_eq!(runs_today.remaining(&at_cap), Some(0));
+		assert_eq!(runs_today.remaining(&below_cap), Some(1));
+		assert_eq!(runs_today.remaining(&uncapped), None);
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn the_cap_only_excludes_runs_due_today() {
+		let connection = test_pool().await;
+		let profile = insert_capped_profile(&connection).await;
+		insert_run(&connection, profile.id(), true, None).await;
+
+		// Midday keeps both instants on the same UTC day.
+		let now = Utc::now()
+			.date_naive()
+			.and_hms_opt(12, 0, 0)
+			.unwrap()
+			.and_utc();
+
+		let runs_today = Profile::runs_today(&connection, std::slice::from_ref(&profile), now)
+			.await
+			.unwrap();
+
+		assert!(profile.cap_excludes(now + TimeDelta::hours(1), now, &runs_today).unwrap());
+		assert!(!profile.cap_excludes(now + TimeDelta::hours(13), now, &runs_today).unwrap());
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn stats_aggregate_the_profile_runs() {
+		let connection = test_pool().await;
+		let profile = insert_profile(&connection, "0 * * * *").await;
+
+		for _ in 0..3 {
+			insert_run(&connection, profile.id(), true, None).await;
+		}
+
+		insert_run(&connection, profile.id(), true, Some("The upload failed.")).await;
+
+		let stats = profile.stats(&connection)
+			.await
+			.unwrap();
+
+		assert_eq!(stats.total_runs, 4);
+		assert_eq!(stats.successful_runs, 3);
+		assert_eq!(stats.success_rate, Some(0.75));
+		assert!(stats.average_duration_secs.is_some());
+		assert!(stats.last_success_at.is_some());
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn stats_of_profiles_without_runs_are_empty() {
+		let connection = test_pool().await;
+		let profile = insert_profile(&connection, "0 * * * *").await;
+
+		let stats = profile.stats(&connection)
+			.await
+			.unwrap();
+
+		assert_eq!(stats.total_runs, 0);
+		assert_eq!(stats.successful_runs, 0);
+		assert_eq!(stats.success_rate, None);
+		assert_eq!(stats.average_duration_secs, None);
+		assert_eq!(stats.last_success_at, None);
+	}
+
+	#[test]
+	fn jitter_is_stable_for_a_profile() {
+		for id in 1..100 {
+			assert_eq!(jitter_offset(id, 600), jitter_offset(id, 600));
+		}
+	}
+
+	#[test]
+	fn jitter_stays_within_the_window() {
+		for id in 1..1000 {
+			let offset = jitter_offset(id, 60).num_seconds();
+			assert!((0..=60).contains(&offset), "{id} got {offset}");
+		}
+
+		assert_eq!(jitter_offset(7, 0), TimeDelta::zero());
+		assert_eq!(jitter_offset(7, -5), TimeDelta::zero());
+	}
+
+	#[test]
+	fn jitter_spreads_consecutive_profiles() {
+		let offsets = (1..=20)
+			.map(|id| jitter_offset(id, 3600))
+			.collect::<std::collections::HashSet<_>>();
+
+		// Twenty ids over 3601 slots, a handful of
+		// collisions is fine, a constant offset is not.
+		assert!(offsets.len() >= 15, "only {} distinct offsets", offsets.len());
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn unpaused_profiles_are_listed_by_priority() {
+		let connection = test_pool().await;
+		let low = insert_profile(&connection, "0 * * * *").await;
+		let mut high = insert_profile(&connection, "0 * * * *").await;
+
+		let update = ProfileUpdate { priority: i32::MAX, ..unchanged(&high) };
+		high.update(&connection, update)
+			.await
+			.expect("The profile is up to date");
+
+		let ids = Profile::list_unpaused(&connection)
+			.await
+			.expect("The profiles can be listed")
+			.into_iter()
+			.map(|profile| profile.id())
+			.collect::<Vec<_>>();
+
+		let position = |id| ids.iter()
+			.position(|listed| *listed == id)
+			.expect("Both profiles are unpaused");
+
+		assert!(position(high.id()) < position(low.id()));
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn updates_flag_schedules_that_never_fire() {
+		let connection = test_pool().await;
+		let mut profile = insert_profile(&connection, "0 * * * *").await;
+		let original = profile.clone();
+
+		// The 30th of February never comes.
+		let update = ProfileUpdate { schedule: "0 0 30 2 *".into(), ..unchanged(&profile) };
+		profile.update(&connection, update)
+			.await
+			.expect("The profile is up to date");
+
+		assert!(profile.schedule_warning());
+		assert_eq!(profile.version(), original.version() + 1);
+		assert!(profile.updated_at() >= original.updated_at());
+
+		let update = ProfileUpdate { schedule: "0 12 * * *".into(), ..unchanged(&profile) };
+		profile.update(&connection, update)
+			.await
+			.expect("The profile is up to date");
+
+		assert!(!profile.schedule_warning());
+		assert_eq!(profile.version(), original.version() + 2);
+	}
+
+	#[test]
+	fn days_start_at_the_local_midnight() {
+		let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+		assert_eq!(
+			day_start("Europe/Madrid".parse().unwrap(), date),
+			"2024-01-14T23:00:00Z".parse::<DateTime<Utc>>().unwrap()
+		);
+	}
+
+	#[test]
+	fn days_whose_midnight_is_skipped_start_after_the_gap() {
+		// Chile moves from 00:00 to 01:00 when DST starts.
+		let date = NaiveDate::from_ymd_opt(2024, 9, 8).unwrap();
+
+		assert_eq!(
+			day_start("America/Santiago".parse().unwrap(), date),
+			"2024-09-08T04:00:00Z".parse::<DateTime<Utc>>().unwrap()
+		);
+	}
+
+	#[test]
+	fn registered_language_tags_are_valid_locales() {
+		for locale in ["en", "en-US", "es-419", "zh-Hant-TW", "sr-Latn-RS"] {
+			assert!(validate_locale(locale).is_ok(), "{locale} was rejected");
+		}
+	}
+
+	#[test]
+	fn malformed_or_unregistered_locales_are_rejected() {
+		for locale in ["", "english", "en_US", "xx-US", "en-US-", "en-UK"] {
+			assert!(
+				matches!(validate_locale(locale), Err(ProfileError::InvalidLocale(_))),
+				"{locale} was accepted"
+			);
+		}
+	}
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{query_as, Error as SqlxError, PgPool};
+use sqlx::prelude::FromRow;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::utils::external::database::timed_query;
+
+
+/// Represents solely server side errors related
+/// to run usage operations.
+#[derive(Debug, Error)]
+pub enum RunUsageError {
+	#[error("Error while querying the database, {0:#}")]
+	DatabaseConnection(#[from] SqlxError)
+}
+
+
+/// The usage a single pipeline step consumed, added
+/// to the run record with [`RunUsage::record`].
+///
+/// Steps fill only what they consume, providers that
+/// don't report usage should set `estimated`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UsageDelta {
+	pub text_tokens: i64,
+	pub tts_characters: i64,
+	pub render_seconds: f64,
+	pub upload_bytes: i64,
+	pub estimated: bool
+}
+
+
+/// The usage of many runs added up.
+#[derive(Serialize, FromRow, ToSchema, Debug, Clone)]
+pub struct RunUsageTotals {
+	/// The text generation tokens, prompt and output.
+	text_tokens: i64,
+
+	/// The characters sent to the TTS provider.
+	tts_characters: i64,
+
+	/// The compute time spent rendering, in seconds.
+	render_seconds: f64,
+
+	/// The bytes sent to upload platforms.
+	upload_bytes: i64
+}
+
+
+/// Model representation for run usage database schema.
+#[derive(Serialize, Deserialize, FromRow, ToSchema, Debug, PartialEq, PartialOrd, Clone)]
+pub struct RunUsage {
+	/// The run this usage belongs to, also
+	/// the primary key for this model.
+	run_id: i32,
+
+	/// The text generation tokens, prompt and output.
+	text_tokens: i64,
+
+	/// The characters sent to the TTS provider.
+	tts_characters: i64,
+
+	/// The compute time spent rendering, in seconds.
+	render_seconds: f64,
+
+	/// The bytes sent to upload platforms.
+	upload_bytes: i64,
+
+	/// Whether any of the figures is an estimate.
+	estimated: bool
+}
+
+impl RunUsage {
+	/// Adds the usage of a step to the run record,
+	/// creating it on the first step that reports.
+	#[allow(dead_code)]
+	pub async fn record(
+		connection: &PgPool,
+		run_id: i32,
+		delta: UsageDelta
+	) -> Result<Self, RunUsageError> {
+		let usage = timed_query(
+			"run_usage.record",
+			query_as(r"
+				INSERT INTO run_usage(
+					run_id, text_tokens, tts_characters,
+					render_seconds, upload_bytes, estimated
+				)
+				VALUES ($1, $2, $3, $4, $5, $6)
+				ON CONFLICT (run_id) DO UPDATE SET
+					text_tokens = run_usage.text_tokens + EXCLUDED.text_tokens,
+					tts_characters = run_usage.tts_characters + EXCLUDED.tts_characters,
+					render_seconds = run_usage.render_seconds + EXCLUDED.render_seconds,
+					upload_bytes = run_usage.upload_bytes + EXCLUDED.upload_bytes,
+					estimated = run_usage.estimated OR EXCLUDED.estimated
+				RETURNING *
+			")
+				.bind(run_id)
+				.bind(delta.text_tokens)
+				.bind(delta.tts_characters)
+				.bind(delta.render_seconds)
+				.bind(delta.upload_bytes)
+				.bind(delta.estimated)
+				.fetch_one(connection)
+		)
+			.await?;
+
+		Ok(usage)
+	}
+
+	/// Obtains the usage of a run, Ok(None) is
+	/// returned if no step reported usage yet.
+	pub async fn get_by_run(
+		connection: &PgPool,
+		run_id: i32
+	) -> Result<Option<Self>, RunUsageError> {
+		let usage = timed_query(
+			"run_usage.get_by_run",
+			query_as(r"
+				SELECT * FROM run_usage
+				WHERE run_id = $1
+			")
+				.bind(run_id)
+				.fetch_optional(connection)
+		)
+			.await?;
+
+		Ok(usage)
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::testing::{insert_profile, insert_run, test_pool};
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn step_usage_is_added_to_the_run_record() {
+		let connection = test_pool().await;
+		let profile = insert_profile(&connection, "0 * * * *").await;
+		let run_id = insert_run(&connection, profile.id(), true, None).await;
+
+		RunUsage::record(&connection, run_id, UsageDelta { text_tokens: 120, ..Default::default() })
+			.await
+			.unwrap();
+
+		RunUsage::record(&connection, run_id, UsageDelta { text_tokens: 30, tts_characters: 400, ..Default::default() })
+			.await
+			.unwrap();
+
+		RunUsage::record(&connection, run_id, UsageDelta { render_seconds: 12.5, estimated: true, ..Default::default() })
+			.await
+			.unwrap();
+
+		let usage = RunUsage::get_by_run(&connection, run_id)
+			.await
+			.unwrap()
+			.expect("The run reported usage");
+
+		assert_eq!(usage.text_tokens, 150);
+		assert_eq!(usage.tts_characters, 400);
+		assert_eq!(usage.render_seconds, 12.5);
+		assert_eq!(usage.upload_bytes, 0);
+		assert!(usage.estimated);
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn runs_without_reported_usage_have_no_record() {
+		let connection = test_pool().await;
+		let profile = insert_profile(&connection, "0 * * * *").await;
+		let run_id = insert_run(&connection, profile.id(), true, None).await;
+
+		assert_eq!(RunUsage::get_by_run(&connection, run_id).await.unwrap(), None);
+	}
+}
@@ -1,10 +1,167 @@
-use chrono::{DateTime, Utc};
+use std::fmt::Display;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::prelude::FromRow;
+use sqlx::{query, query_as, Error as SqlxError, PgPool};
+use sqlx::prelude::{FromRow, Type};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::models::run_usage::RunUsageTotals;
+use crate::utils::external::database::timed_query;
+use crate::utils::extractors::list::ListParams;
+
+
+/// The error stored on runs that were still in progress
+/// when the process stopped, see `Run::sweep_interrupted`.
+const INTERRUPTED_RUN_ERROR: &str = "interrupted by restart";
+
+/// Represents solely server side errors related
+/// to run operations.
+#[derive(Debug, Error)]
+pub enum RunError {
+	#[error("Error while querying the database, {0:#}")]
+	DatabaseConnection(#[from] SqlxError)
+}
+
+
+/// The category of a run failure, this tells
+/// transient failures from permanent ones.
+#[derive(Serialize, Deserialize, Type, ToSchema, Debug, PartialEq, PartialOrd, Clone, Copy)]
+#[sqlx(type_name = "run_error_kind", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RunErrorKind {
+	/// A temporary failure, such as a lost database
+	/// connection, that may succeed if retried.
+	Transient,
+
+	/// The profile or environment is misconfigured,
+	/// retrying fails the same way until it's fixed.
+	Configuration,
+
+	/// An external provider failed or returned
+	/// something unusable.
+	Provider,
+
+	/// A bug or an unexpected server side failure.
+	Internal
+}
+
+impl RunErrorKind {
+	/// Whether a run that failed with this
+	/// kind may succeed if retried.
+	#[allow(dead_code)]
+	pub fn is_retryable(&self) -> bool {
+		matches!(self, Self::Transient | Self::Provider)
+	}
+}
+
+
+/// An error that can make a run fail, it's stored
+/// as its `Display` together with its kind.
+pub trait RunErrorSource: Display {
+	/// The category this error is stored as.
+	fn kind(&self) -> RunErrorKind;
+}
+
+impl RunErrorSource for RunError {
+	fn kind(&self) -> RunErrorKind {
+		match self {
+			Self::DatabaseConnection(_) => RunErrorKind::Transient
+		}
+	}
+}
+
+
+/// A lightweight view of an errored run.
+#[derive(Serialize, FromRow, ToSchema, Debug, Clone)]
+pub struct RunFailure {
+	/// The run that errored.
+	id: i32,
+
+	/// The profile the run belongs to.
+	profile_id: i32,
+
+	/// The error `Display` stored by the run.
+	error: String,
+
+	/// The category of the error, None for runs
+	/// that errored before it was recorded.
+	error_kind: Option<RunErrorKind>
+}
+
+
+/// How many runs started on a given UTC day.
+#[derive(Serialize, FromRow, ToSchema, Debug, Clone)]
+pub struct RunsPerDay {
+	/// The UTC day the runs started on.
+	day: NaiveDate,
+
+	/// Every run started that day.
+	total: i64,
+
+	/// The runs started that day that errored.
+	errored: i64
+}
+
+
+/// A profile ranked by how many runs it started.
+#[derive(Serialize, FromRow, ToSchema, Debug, Clone)]
+pub struct BusyProfile {
+	/// The ranked profile.
+	profile_id: i32,
+
+	/// The profile human readable identifier.
+	name: String,
+
+	/// How many runs the profile started in the window.
+	runs: i64
+}
+
+
+/// System wide run analytics over the last `days` days.
+///
+/// The JSON structure is the following
+/// ```json
+/// {
+///     "days": 30,
+///     "runs_per_day": [{ "day": "2025-01-01", "total": 4, "errored": 1 }],
+///     "success_rate": 0.75 | null,
+///     "busiest_profiles": [{ "profile_id": 1, "name": "...", "runs": 4 }],
+///     "usage": {
+///         "text_tokens": 0,
+///         "tts_characters": 0,
+///         "render_seconds": 0.0,
+///         "upload_bytes": 0
+///     }
+/// }
+/// ```
+///
+/// Days without runs are omitted from `runs_per_day`.
+#[derive(Serialize, ToSchema, Debug, Clone)]
+pub struct RunAnalytics {
+	/// The window the analytics were computed over.
+	days: i32,
+
+	/// The runs started per UTC day, oldest first.
+	runs_per_day: Vec<RunsPerDay>,
+
+	/// The successful runs over the runs that finished
+	/// or errored in the window, None if none did.
+	success_rate: Option<f64>,
+
+	/// The profiles that started the most runs
+	/// in the window, busiest first.
+	busiest_profiles: Vec<BusyProfile>,
+
+	/// The usage of the runs in the window added up.
+	usage: RunUsageTotals
+}
 
 
 /// Model representation for runs database schema.
-#[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, PartialOrd, Clone)]
+#[derive(Serialize, Deserialize, FromRow, ToSchema, Debug, PartialEq, PartialOrd, Clone)]
 pub struct Run {
 	/// The primary key for this model.
 	id: i32,
@@ -16,20 +173,308 @@ pub struct Run {
 	/// is going to be stored here.
 	error: Option<String>,
 
+	/// The category of the error, set together
+	/// with it by [`Run::set_error`].
+	error_kind: Option<RunErrorKind>,
+
 	/// What layer is being processed at this moment, the
 	/// format is `stage.layer`.
 	processing: Vec<String>,
 
+	/// The question generated for this run, None
+	/// until the text generation step finished.
+	generated_question: Option<String>,
+
+	/// The answer generated for this run, None
+	/// until the text generation step finished.
+	generated_answer: Option<String>,
+
+	/// The directory the run intermediate artifacts
+	/// are written to, kept for debugging.
+	work_dir: Option<String>,
+
 	/// When did this start running, this is used
 	/// by the scheduler to know if it should start a new run.
 	started_at: DateTime<Utc>,
 
 	/// When did this end running, this is used
-	/// by the UI to display the running state.
-	finished_at: DateTime<Utc>
+	/// by the UI to display the running state,
+	/// None while the run is in progress.
+	finished_at: Option<DateTime<Utc>>
 }
 
 impl Run {
+	/// Counts the runs that didn't finish yet.
+	pub async fn count_active(connection: &PgPool) -> Result<i64, RunError> {
+		let (active,): (i64,) = timed_query(
+			"runs.count_active",
+			query_as(r"
+				SELECT COUNT(*) FROM runs
+				WHERE finished_at IS NULL
+			")
+				.fetch_one(connection)
+		)
+			.await?;
+
+		Ok(active)
+	}
+
+	/// The runs of a profile, newest first, paginated
+	/// with the `limit`, `offset` and `before` cursor.
+	pub async fn list_by_profile(
+		connection: &PgPool,
+		profile_id: i32,
+		params: &ListParams
+	) -> Result<Vec<Self>, RunError> {
+		let runs = timed_query(
+			"runs.list_by_profile",
+			query_as(r"
+				SELECT * FROM runs
+				WHERE profile_id = $1
+				AND ($2::INT IS NULL OR id < $2)
+				ORDER BY id DESC
+				LIMIT $3 OFFSET $4
+			")
+				.bind(profile_id)
+				.bind(params.before())
+				.bind(params.limit())
+				.bind(params.offset())
+				.fetch_all(connection)
+		)
+			.await?;
+
+		Ok(runs)
+	}
+
+	/// The latest errored runs, newest first.
+	pub async fn recent_failures(
+		connection: &PgPool,
+		limit: i64
+	) -> Result<Vec<RunFailure>, RunError> {
+		let failures = timed_query(
+			"runs.recent_failures",
+			query_as(r"
+				SELECT id, profile_id, error, error_kind FROM runs
+				WHERE error IS NOT NULL
+				ORDER BY started_at DESC, id DESC
+				LIMIT $1
+			")
+				.bind(limit)
+				.fetch_all(connection)
+		)
+			.await?;
+
+		Ok(failures)
+	}
+
+
+	/// Computes the system wide [`RunAnalytics`] for the runs
+	/// started in the last `days` days, returning at most
+	/// `busiest_limit` busiest profiles.
+	///
+	/// Every query ranges over `started_at`, which is indexed.
+	pub async fn analytics(
+		connection: &PgPool,
+		days: i32,
+		busiest_limit: i64
+	) -> Result<RunAnalytics, RunError> {
+		let runs_per_day = timed_query(
+			"runs.analytics.runs_per_day",
+			query_as(r"
+				SELECT
+					(started_at AT TIME ZONE 'UTC')::DATE AS day,
+					COUNT(*) AS total,
+					COUNT(*) FILTER (WHERE error IS NOT NULL) AS errored
+				FROM runs
+				WHERE started_at >= NOW() - MAKE_INTERVAL(days => $1)
+				GROUP BY day
+				ORDER BY day
+			")
+				.bind(days)
+				.fetch_all(connection)
+		)
+			.await?;
+
+		let (success_rate,): (Option<f64>,) = timed_query(
+			"runs.analytics.success_rate",
+			query_as(r"
+				SELECT
+					COUNT(*) FILTER (
+						WHERE finished_at IS NOT NULL AND error IS NULL
+					)::FLOAT8 / NULLIF(COUNT(*) FILTER (
+						WHERE finished_at IS NOT NULL OR error IS NOT NULL
+					), 0)
+				FROM runs
+				WHERE started_at >= NOW() - MAKE_INTERVAL(days => $1)
+			")
+				.bind(days)
+				.fetch_one(connection)
+		)
+			.await?;
+
+		let busiest_profiles = timed_query(
+			"runs.analytics.busiest_profiles",
+			query_as(r"
+				SELECT runs.profile_id, profiles.name, COUNT(*) AS runs
+				FROM runs
+				INNER JOIN profiles ON profiles.id = runs.profile_id
+				WHERE runs.started_at >= NOW() - MAKE_INTERVAL(days => $1)
+				GROUP BY runs.profile_id, profiles.name
+				ORDER BY runs DESC, runs.profile_id
+				LIMIT $2
+			")
+				.bind(days)
+				.bind(busiest_limit)
+				.fetch_all(connection)
+		)
+			.await?;
+
+		let usage = timed_query(
+			"runs.analytics.usage",
+			query_as(r"
+				SELECT
+					COALESCE(SUM(run_usage.text_tokens), 0)::BIGINT AS text_tokens,
+					COALESCE(SUM(run_usage.tts_characters), 0)::BIGINT AS tts_characters,
+					COALESCE(SUM(run_usage.render_seconds), 0) AS render_seconds,
+					COALESCE(SUM(run_usage.upload_bytes), 0)::BIGINT AS upload_bytes
+				FROM run_usage
+				INNER JOIN runs ON runs.id = run_usage.run_id
+				WHERE runs.started_at >= NOW() - MAKE_INTERVAL(days => $1)
+			")
+				.bind(days)
+				.fetch_one(connection)
+		)
+			.await?;
+
+		Ok(RunAnalytics {
+			days,
+			runs_per_day,
+			success_rate,
+			busiest_profiles,
+			usage
+		})
+	}
+
+
+	/// Stores the directory the run writes its intermediate
+	/// artifacts to, see `RunWorkDir`.
+	#[allow(dead_code)]
+	pub async fn set_work_dir(
+		&mut self,
+		connection: &PgPool,
+		work_dir: &Path
+	) -> Result<(), RunError> {
+		let work_dir = work_dir
+			.to_string_lossy()
+			.into_owned();
+
+		timed_query(
+			"runs.set_work_dir",
+			query(r"
+				UPDATE runs
+				SET work_dir = $2
+				WHERE id = $1
+			")
+				.bind(self.id)
+				.bind(&work_dir)
+				.execute(connection)
+		)
+			.await?;
+
+		self.work_dir = Some(work_dir);
+
+		Ok(())
+	}
+
+	/// Stores the generated text, this must be called by
+	/// the text generation step once it finished.
+	#[allow(dead_code)]
+	pub async fn set_generated_text(
+		&mut self,
+		connection: &PgPool,
+		question: String,
+		answer: String
+	) -> Result<(), RunError> {
+		timed_query(
+			"runs.set_generated_text",
+			query(r"
+				UPDATE runs
+				SET generated_question = $2, generated_answer = $3
+				WHERE id = $1
+			")
+				.bind(self.id)
+				.bind(&question)
+				.bind(&answer)
+				.execute(connection)
+		)
+			.await?;
+
+		self.generated_question = Some(question);
+		self.generated_answer = Some(answer);
+
+		Ok(())
+	}
+
+	/// Marks the run as errored, storing the error
+	/// `Display` together with its kind, and as
+	/// finished since an errored run is terminal.
+	///
+	/// Runs only fail inside the pipeline, which
+	/// the binary doesn't spawn yet.
+	#[allow(dead_code)]
+	pub async fn set_error(
+		&mut self,
+		connection: &PgPool,
+		error: &impl RunErrorSource
+	) -> Result<(), RunError> {
+		let message = error.to_string();
+		let kind = error.kind();
+
+		let (finished_at,): (DateTime<Utc>,) = timed_query(
+			"runs.set_error",
+			query_as(r"
+				UPDATE runs
+				SET error = $2, error_kind = $3, finished_at = NOW()
+				WHERE id = $1
+				RETURNING finished_at
+			")
+				.bind(self.id)
+				.bind(&message)
+				.bind(kind)
+				.fetch_one(connection)
+		)
+			.await?;
+
+		self.error = Some(message);
+		self.error_kind = Some(kind);
+		self.finished_at = Some(finished_at);
+
+		Ok(())
+	}
+
+	/// Marks every run that never finished as errored, this
+	/// must run on startup before any run is spawned, since
+	/// at that point no unfinished run has a process behind it.
+	///
+	/// Returns how many runs were swept.
+	pub async fn sweep_interrupted(connection: &PgPool) -> Result<u64, RunError> {
+		let result = timed_query(
+			"runs.sweep_interrupted",
+			query(r"
+				UPDATE runs
+				SET error = $1, error_kind = $2, finished_at = NOW()
+				WHERE finished_at IS NULL
+			")
+				.bind(INTERRUPTED_RUN_ERROR)
+				.bind(RunErrorKind::Internal)
+				.execute(connection)
+		)
+			.await?;
+
+		Ok(result.rows_affected())
+	}
+
+
 	/// The primary key for this model.
     pub fn id(&self) -> i32 {
         self.id
@@ -46,21 +491,95 @@ impl Run {
         self.error.as_ref()
     }
 
+	/// The category of the error, set together
+	/// with it by [`Run::set_error`].
+    pub fn error_kind(&self) -> Option<RunErrorKind> {
+        self.error_kind
+    }
+
 	/// What layer is being processed at this moment, the
 	/// format is `stage.layer`.
+    #[allow(dead_code)]
     pub fn processing(&self) -> &[String] {
         &self.processing
     }
 
+	/// The question generated for this run, None
+	/// until the text generation step finished.
+    #[allow(dead_code)]
+    pub fn generated_question(&self) -> Option<&str> {
+        self.generated_question.as_deref()
+    }
+
+	/// The answer generated for this run, None
+	/// until the text generation step finished.
+    #[allow(dead_code)]
+    pub fn generated_answer(&self) -> Option<&str> {
+        self.generated_answer.as_deref()
+    }
+
+	/// The directory the run intermediate artifacts
+	/// are written to, kept for debugging.
+    #[allow(dead_code)]
+    pub fn work_dir(&self) -> Option<&str> {
+        self.work_dir.as_deref()
+    }
+
 	/// When did this start running, this is used
 	/// by the scheduler to know if it should start a new run.
+    #[allow(dead_code)]
     pub fn started_at(&self) -> DateTime<Utc> {
         self.started_at
     }
 
 	/// When did this end running, this is used
-	/// by the UI to display the running state.
-    pub fn finished_at(&self) -> DateTime<Utc> {
+	/// by the UI to display the running state,
+	/// None while the run is in progress.
+    #[allow(dead_code)]
+    pub fn finished_at(&self) -> Option<DateTime<Utc>> {
         self.finished_at
     }
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::testing::{insert_profile, insert_run, test_pool};
+
+	async fn get_run(connection: &PgPool, id: i32) -> Run {
+		query_as("SELECT * FROM runs WHERE id = $1")
+			.bind(id)
+			.fetch_one(connection)
+			.await
+			.expect("The test run exists")
+	}
+
+	#[actix_web::test]
+	#[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+	async fn sweep_marks_only_unfinished_runs_as_interrupted() {
+		let connection = test_pool().await;
+		let profile = insert_profile(&connection, "0 * * * *").await;
+
+		let unfinished = insert_run(&connection, profile.id(), false, None).await;
+		let finished = insert_run(&connection, profile.id(), true, None).await;
+		let errored = insert_run(&connection, profile.id(), true, Some("boom")).await;
+
+		let swept = Run::sweep_interrupted(&connection)
+			.await
+			.expect("The sweep succeeds");
+
+		assert!(swept >= 1);
+
+		let unfinished = get_run(&connection, unfinished).await;
+		assert_eq!(unfinished.error().map(String::as_str), Some(INTERRUPTED_RUN_ERROR));
+		assert_eq!(unfinished.error_kind(), Some(RunErrorKind::Internal));
+		assert!(unfinished.finished_at().is_some());
+
+		let finished = get_run(&connection, finished).await;
+		assert_eq!(finished.error(), None);
+		assert_eq!(finished.error_kind(), None);
+
+		let errored = get_run(&connection, errored).await;
+		assert_eq!(errored.error().map(String::as_str), Some("boom"));
+	}
+}
@@ -5,7 +5,8 @@ use sqlx::prelude::{FromRow, Type};
 #[derive(Serialize, Deserialize, Type, Debug, PartialEq, PartialOrd, Clone, Copy)]
 #[sqlx(type_name = "upload_platform_type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum UploadPlatformType {
-	/// The provider defined in the environment variables.
+	/// The provider defined in the environment variables,
+	/// videos are written to `RYT_OUTPUT_DIR`.
 	Local,
 
 	/// YouTube Short Video Upload.
@@ -14,8 +15,21 @@ pub enum UploadPlatformType {
 	YoutubeVideo
 }
 
+impl UploadPlatformType {
+	/// The default output resolution as `(width, height)`
+	/// for profiles targeting this platform.
+	#[allow(dead_code)]
+	pub fn default_resolution(&self) -> (i32, i32) {
+		match self {
+			Self::Local | Self::YoutubeShorts => (1080, 1920),
+			Self::YoutubeVideo => (1920, 1080)
+		}
+	}
+}
+
 /// Model representation for upload platforms database schema.
 #[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, PartialOrd, Clone)]
+#[allow(dead_code)]
 pub struct UploadPlatform {
 	/// The primary key for this model.
 	id: i32,
@@ -33,6 +47,7 @@ pub struct UploadPlatform {
 	oauth_token: Vec<u8>
 }
 
+#[allow(dead_code)]
 impl UploadPlatform {
 	/// The primary key for this model.
     pub fn id(&self) -> i32 {
@@ -5,6 +5,7 @@ use sqlx::prelude::FromRow;
 
 /// Model representation for uploads database schema.
 #[derive(Serialize, Deserialize, FromRow, Debug, PartialEq, PartialOrd, Clone)]
+#[allow(dead_code)]
 pub struct Uploads {
 	/// The primary key for this model.
 	id: i32,
@@ -15,13 +16,21 @@ pub struct Uploads {
 	/// The run this upload belongs to.
 	run_id: i32,
 
-	/// The URL generated by the upload platform provider.
-	generated_url: i32,
+	/// The URL generated by the upload platform provider,
+	/// a `file://` URL for the `Local` platform.
+	generated_url: String,
 
 	/// When was this uploaded.
-	uploaded_at: DateTime<Utc>
+	uploaded_at: DateTime<Utc>,
+
+	/// When is this scheduled to be published, uploads
+	/// with a publish time are uploaded as private and
+	/// published by the platform, if None the upload
+	/// was published right away.
+	publish_at: Option<DateTime<Utc>>
 }
 
+#[allow(dead_code)]
 impl Uploads {
 	/// The primary key for this model.
     pub fn id(&self) -> i32 {
@@ -38,13 +47,31 @@ impl Uploads {
         self.run_id
     }
 
-	/// The URL generated by the upload platform provider.
-    pub fn generated_url(&self) -> i32 {
-        self.generated_url
+	/// The URL generated by the upload platform provider,
+	/// a `file://` URL for the `Local` platform.
+    pub fn generated_url(&self) -> &str {
+        &self.generated_url
     }
 
 	/// When was this uploaded.
     pub fn uploaded_at(&self) -> DateTime<Utc> {
         self.uploaded_at
     }
+
+	/// When is this scheduled to be published, uploads
+	/// with a publish time are uploaded as private and
+	/// published by the platform, if None the upload
+	/// was published right away.
+    #[allow(dead_code)]
+    pub fn publish_at(&self) -> Option<DateTime<Utc>> {
+        self.publish_at
+    }
+
+	/// Whether the upload is still private waiting
+	/// for its scheduled publish time.
+    #[allow(dead_code)]
+    pub fn is_pending_publish(&self) -> bool {
+        self.publish_at
+            .is_some_and(|publish_at| publish_at > Utc::now())
+    }
 }
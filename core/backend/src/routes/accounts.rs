@@ -0,0 +1,151 @@
+use actix_failwrap::proof_route;
+use actix_web::web::{Data, Json};
+use actix_web::HttpResponse;
+use actix_web::http::StatusCode;
+use email_address::EmailAddress;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::models::accounts::{
+    Account,
+    AccountCreationResult,
+    AccountCredentials,
+    AccountError,
+    PasswordPolicy,
+    PasswordRejection
+};
+use crate::utils::application::context::AppContext;
+use crate::utils::application::errors::{http_error, ErrorBody, ErrorCode, HttpError};
+use crate::utils::extractors::authentication::OptionalAuth;
+
+/// Holds errors related to accounts trough HTTP.
+#[derive(Error, Debug)]
+enum AccountsRequestError {
+    #[error("Invalid or not provided credentials.")]
+    Unauthorized,
+
+    #[error("The email is not valid.")]
+    InvalidEmail,
+
+    #[error("An account with this email already exists.")]
+    AlreadyExists,
+
+    #[error("{0}")]
+    WeakPassword(PasswordRejection),
+
+    #[error("Couldn't create the account, {0:#}")]
+    Account(#[from] AccountError)
+}
+
+impl HttpError for AccountsRequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::AlreadyExists => StatusCode::CONFLICT,
+            Self::InvalidEmail | Self::WeakPassword(_) => StatusCode::BAD_REQUEST,
+            Self::Account(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::InvalidEmail => ErrorCode::InvalidEmail,
+            Self::AlreadyExists => ErrorCode::AccountAlreadyExists,
+            Self::WeakPassword(_) => ErrorCode::WeakPassword,
+            Self::Account(AccountError::DatabaseConnection(_)) => ErrorCode::Database,
+            Self::Account(AccountError::PasswordHash(_)) => ErrorCode::Internal
+        }
+    }
+}
+
+http_error!(AccountsRequestError);
+
+/// The OpenAPI specification for this module.
+#[derive(OpenApi)]
+#[openapi(paths(create_account_route))]
+pub struct AccountsApi;
+
+/// The credentials of the account to create.
+#[derive(Deserialize, ToSchema, Debug)]
+struct CreateAccountRequest {
+    email: String,
+    password: String
+}
+
+/// The created account, the password
+/// hash is never sent back.
+#[derive(Serialize, ToSchema, Debug)]
+struct CreateAccountResponse {
+    id: i32,
+    email: String
+}
+
+/// Creates an operator account, only authenticated
+/// operators may create accounts.
+///
+/// The email must be valid and contain no colons, as
+/// basic credentials are split on the first one.
+///
+/// The password must meet the password policy, see
+/// `RYT_PASSWORD_MIN_LENGTH`, otherwise the specific
+/// requirement that failed is returned.
+///
+/// This is registered as a service on its own since
+/// an empty scope would shadow any later service.
+#[utoipa::path(
+    post,
+    path = "/accounts",
+    tag = "accounts",
+    request_body = CreateAccountRequest,
+    responses(
+        (status = 201, description = "The account was created.", body = CreateAccountResponse),
+        (status = 400, description = "The email is not valid or the password doesn't meet the policy.", body = ErrorBody),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 409, description = "An account with this email already exists.", body = ErrorBody),
+        (status = 500, description = "The account couldn't be created.", body = ErrorBody)
+    )
+)]
+#[proof_route("POST /accounts")]
+pub async fn create_account_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    body: Json<CreateAccountRequest>
+) -> Result<HttpResponse, AccountsRequestError> {
+    if !auth.is_authenticated() {
+        return Err(AccountsRequestError::Unauthorized);
+    }
+
+    let CreateAccountRequest { email, password } = body.into_inner();
+
+    // The email is everything before the first colon
+    // of basic credentials, so it can't contain any.
+    if !EmailAddress::is_valid(&email) || email.contains(':') {
+        return Err(AccountsRequestError::InvalidEmail);
+    }
+
+    let result = Account::create_account(
+        &context.get_db_connection(),
+        AccountCredentials::Basic {
+            email,
+            password: password.into_bytes()
+        },
+        &PasswordPolicy::from_config(context.config())
+    )
+        .await?;
+
+    match result {
+        AccountCreationResult::Created(account) => Ok(
+            HttpResponse::Created()
+                .json(CreateAccountResponse {
+                    id: account.id(),
+                    email: account.email().to_string()
+                })
+        ),
+
+        AccountCreationResult::AlreadyExists => Err(AccountsRequestError::AlreadyExists),
+
+        AccountCreationResult::Rejected(rejection) => Err(AccountsRequestError::WeakPassword(rejection))
+    }
+}
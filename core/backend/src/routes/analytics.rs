@@ -0,0 +1,130 @@
+use actix_failwrap::proof_route;
+use actix_web::web::{Data, Query};
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::http::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+use utoipa::OpenApi;
+
+use crate::models::runs::{Run, RunAnalytics, RunError};
+use crate::utils::application::context::AppContext;
+use crate::utils::application::errors::{http_error, ErrorBody, ErrorCode, HttpError};
+use crate::utils::extractors::authentication::OptionalAuth;
+
+/// The window analytics are computed over
+/// when `days` is not provided.
+const DEFAULT_ANALYTICS_DAYS: i32 = 30;
+/// The widest window analytics may be computed over.
+const MAX_ANALYTICS_DAYS: i32 = 365;
+/// How many profiles are ranked as the busiest.
+const BUSIEST_PROFILES_LIMIT: i64 = 5;
+
+/// Holds errors related to analytics trough HTTP.
+#[derive(Error, Debug)]
+enum AnalyticsRequestError {
+    #[error("Invalid or not provided credentials.")]
+    Unauthorized,
+
+    #[error("Invalid query parameters, {0}")]
+    InvalidQuery(String),
+
+    #[error("The days must be between 1 and {MAX_ANALYTICS_DAYS}.")]
+    DaysOutOfBounds,
+
+    #[error("Couldn't compute the analytics, {0:#}")]
+    Run(#[from] RunError)
+}
+
+impl HttpError for AnalyticsRequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::InvalidQuery(_) | Self::DaysOutOfBounds => StatusCode::BAD_REQUEST,
+            Self::Run(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::InvalidQuery(_) => ErrorCode::InvalidQuery,
+            Self::DaysOutOfBounds => ErrorCode::DaysOutOfBounds,
+            Self::Run(_) => ErrorCode::Database
+        }
+    }
+}
+
+http_error!(AnalyticsRequestError);
+
+/// The OpenAPI specification for this module.
+#[derive(OpenApi)]
+#[openapi(paths(analytics_route))]
+pub struct AnalyticsApi;
+
+/// The analytics query parameters.
+#[derive(Deserialize, Debug)]
+struct AnalyticsQuery {
+    days: Option<i32>
+}
+
+/// Returns the system wide run analytics for the
+/// overview page, see `RunAnalytics` for the shape.
+///
+/// Results are cached per `days` for `RYT_ANALYTICS_TTL`
+/// seconds, as the overview is polled and read heavy.
+///
+/// This is registered as a service on its own since
+/// an empty scope would shadow any later service.
+#[utoipa::path(
+    get,
+    path = "/analytics",
+    tag = "analytics",
+    params(("days" = Option<i32>, Query, description = "The window in days, 30 by default, at most 365.")),
+    responses(
+        (status = 200, description = "The system wide analytics.", body = RunAnalytics),
+        (status = 400, description = "Invalid query parameters.", body = ErrorBody),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 500, description = "The analytics couldn't be computed.", body = ErrorBody)
+    )
+)]
+#[proof_route("GET /analytics")]
+pub async fn analytics_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    request: HttpRequest
+) -> Result<HttpResponse, AnalyticsRequestError> {
+    if !auth.is_authenticated() {
+        return Err(AnalyticsRequestError::Unauthorized);
+    }
+
+    let Query(query) = Query::<AnalyticsQuery>::from_query(request.query_string())
+        .map_err(|error| AnalyticsRequestError::InvalidQuery(error.to_string()))?;
+
+    let days = query.days.unwrap_or(DEFAULT_ANALYTICS_DAYS);
+    if !(1..=MAX_ANALYTICS_DAYS).contains(&days) {
+        return Err(AnalyticsRequestError::DaysOutOfBounds);
+    }
+
+    let cache = context.analytics_cache();
+
+    let analytics = match cache.get(&days) {
+        Some(analytics) => analytics,
+
+        None => {
+            let analytics = Run::analytics(
+                &context.get_db_connection(),
+                days,
+                BUSIEST_PROFILES_LIMIT
+            )
+                .await?;
+
+            cache.insert(days, analytics.clone());
+            analytics
+        }
+    };
+
+    Ok(
+        HttpResponse::Ok()
+            .json(analytics)
+    )
+}
@@ -1,44 +1,153 @@
-use actix_failwrap::{proof_route, ErrorResponse};
+use actix_failwrap::proof_route;
 use actix_web::cookie::Cookie;
 use actix_web::cookie::time::Duration;
-use actix_web::{HttpResponse, Scope};
-use actix_web::web::scope;
+use actix_web::{HttpRequest, HttpResponse, Scope};
+use actix_web::web::{scope, Data, Json};
+use actix_web::http::StatusCode;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use utoipa::{OpenApi, ToSchema};
 
-use crate::utils::application::errors::json_formatter;
-use crate::utils::extractors::authentication::{OptionalAuth, COOKIE_KEY};
+use crate::models::accounts::AccountError;
+use crate::utils::application::context::AppContext;
+use crate::utils::application::environment::ReddytConfig;
+use crate::utils::application::errors::{http_error, ErrorBody, ErrorCode, HttpError};
+use crate::utils::extractors::authentication::{
+    decode_token,
+    OptionalAuth,
+    AUTH_EXPIRATION_HOURS,
+    COOKIE_KEY
+};
+
+/// How long the authentication cookie lives, derived from
+/// the JWT expiration so both lifetimes can't diverge.
+const COOKIE_MAX_AGE: Duration = Duration::hours(AUTH_EXPIRATION_HOURS);
 
 /// Holds errors related to authentication trough HTTP.
-#[derive(ErrorResponse, Error, Debug)]
-#[transform_response(json_formatter)]
+#[derive(Error, Debug)]
 enum AuthenticationRequestError {
     #[error("Invalid or not provided credentials.")]
-    #[status_code(401)]
-    Unauthorized
+    Unauthorized,
+
+    #[error("Couldn't query the token account, {0:#}")]
+    Account(#[from] AccountError)
+}
+
+impl HttpError for AuthenticationRequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Account(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::Account(AccountError::DatabaseConnection(_)) => ErrorCode::Database,
+            Self::Account(AccountError::PasswordHash(_)) => ErrorCode::Internal
+        }
+    }
 }
 
+http_error!(AuthenticationRequestError);
+
+/// The OpenAPI specification for this module.
+#[derive(OpenApi)]
+#[openapi(paths(login_route, logout_route, me_route, introspect_route))]
+pub struct AuthenticationApi;
+
 /// The exported scope for this module,
-/// it contains login and logout for the admin
-/// panel.
+/// it contains login, logout and the session
+/// identity for the admin panel.
 pub fn authentication_scope() -> Scope {
     scope("/authentication")
         .service(login_route)
         .service(logout_route)
+        .service(me_route)
+        .service(introspect_route)
+}
+
+/// The identity of the authenticated operator.
+#[derive(Serialize, ToSchema, Debug)]
+struct MeResponse {
+    email: String,
+    exp: usize
+}
+
+/// The token to introspect, if not provided
+/// the session cookie is introspected.
+#[derive(Deserialize, ToSchema, Debug)]
+struct IntrospectRequest {
+    token: Option<String>
+}
+
+/// What the server thinks about a token, the
+/// claims are only present for active tokens
+/// and the reason only for inactive ones.
+#[derive(Serialize, ToSchema, Debug, Default)]
+struct IntrospectResponse {
+    active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exp: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seconds_remaining: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>
+}
+
+/// Builds the authentication cookie with the configured
+/// `SameSite` and domain attributes.
+///
+/// The cookie is `Secure` in release builds, or always
+/// if `SameSite=None` is configured, as browsers reject
+/// it otherwise.
+fn authentication_cookie(config: &ReddytConfig, value: String) -> Cookie<'static> {
+    let same_site = config.cookie_same_site();
+
+    let mut cookie = Cookie::build(COOKIE_KEY, value)
+        .path("/")
+        .http_only(true)
+        .secure(
+            cfg!(not(debug_assertions))
+                || same_site.is_some_and(|same_site| same_site.requires_secure())
+        )
+        .max_age(COOKIE_MAX_AGE);
+
+    if let Some(same_site) = same_site {
+        cookie = cookie.same_site(same_site.same_site());
+    }
+
+    if let Some(domain) = config.cookie_domain() {
+        cookie = cookie.domain(domain.to_string());
+    }
+
+    cookie.finish()
 }
 
 /// This route makes use of the `OptionalAuth` middleware
 /// to generate a JWT, if applicable sets the JWT as a
 /// cookie.
+#[utoipa::path(
+    post,
+    path = "/authentication/login",
+    tag = "authentication",
+    responses(
+        (status = 204, description = "Authenticated, the session cookie is set."),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody)
+    )
+)]
 #[proof_route("POST /login")]
-async fn login_route(auth: OptionalAuth) -> Result<HttpResponse, AuthenticationRequestError> {
+async fn login_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>
+) -> Result<HttpResponse, AuthenticationRequestError> {
     match auth.token() {
         Some(token) => {
-            let cookie = Cookie::build(COOKIE_KEY, token)
-                .path("/")
-                .http_only(true)
-                .secure(cfg!(not(debug_assertions)))
-                .max_age(Duration::hours(3))
-                .finish();
+            let cookie = authentication_cookie(context.config(), token.to_string());
 
             Ok(
                 HttpResponse::NoContent()
@@ -55,15 +164,30 @@ async fn login_route(auth: OptionalAuth) -> Result<HttpResponse, AuthenticationR
 /// to know whether the user is authenticated or not
 /// and removes the cookie if it's the case.
 ///
+/// The removal cookie shares the path and domain of the
+/// authentication cookie, otherwise browsers keep it.
+///
 /// XXX: This does not check the authentication origin,
 /// setting a cookie as removal is non-fallible, but
 /// may want to validate for future proofing.
+#[utoipa::path(
+    post,
+    path = "/authentication/logout",
+    tag = "authentication",
+    responses(
+        (status = 204, description = "The session cookie is removed."),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody)
+    )
+)]
 #[proof_route("POST /logout")]
-async fn logout_route(auth: OptionalAuth) -> Result<HttpResponse, AuthenticationRequestError> {
+async fn logout_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>
+) -> Result<HttpResponse, AuthenticationRequestError> {
     auth.token()
         .map(|_| HttpResponse::NoContent()
             .cookie({
-                let mut cookie = Cookie::named(COOKIE_KEY);
+                let mut cookie = authentication_cookie(context.config(), String::new());
                 cookie.make_removal();
                 cookie
             })
@@ -71,3 +195,199 @@ async fn logout_route(auth: OptionalAuth) -> Result<HttpResponse, Authentication
         )
         .ok_or(AuthenticationRequestError::Unauthorized)
 }
+
+/// Returns the identity of the authenticated operator,
+/// so the panel can render who is logged in.
+///
+/// The identity is read from the JWT claims decoded
+/// by `OptionalAuth`, the database is not queried.
+#[utoipa::path(
+    get,
+    path = "/authentication/me",
+    tag = "authentication",
+    responses(
+        (status = 200, description = "The authenticated operator.", body = MeResponse),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody)
+    )
+)]
+#[proof_route("GET /me")]
+async fn me_route(auth: OptionalAuth) -> Result<HttpResponse, AuthenticationRequestError> {
+    let claims = auth
+        .claims()
+        .ok_or(AuthenticationRequestError::Unauthorized)?;
+
+    Ok(
+        HttpResponse::Ok()
+            .json(MeResponse {
+                email: claims.email().to_string(),
+                exp: claims.exp()
+            })
+    )
+}
+
+/// Reports whether a token is accepted and until when,
+/// meant for diagnosing unexpected logouts.
+///
+/// The token is taken from the body or otherwise from
+/// the session cookie, and decoded trough the same path
+/// as bearer authentication, keys are never exposed.
+#[utoipa::path(
+    post,
+    path = "/authentication/introspect",
+    tag = "authentication",
+    request_body(content = Option<IntrospectRequest>, content_type = "application/json"),
+    responses(
+        (status = 200, description = "The token state.", body = IntrospectResponse),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody)
+    )
+)]
+#[proof_route("POST /introspect")]
+async fn introspect_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    request: HttpRequest,
+    // `Option` never rejects, the override only satisfies `proof_route`.
+    #[error_override(Unauthorized)] body: Option<Json<IntrospectRequest>>
+) -> Result<HttpResponse, AuthenticationRequestError> {
+    if !auth.is_authenticated() {
+        return Err(AuthenticationRequestError::Unauthorized);
+    }
+
+    let token = body
+        .and_then(|body| body.into_inner().token)
+        .or_else(|| request
+            .cookie(COOKIE_KEY)
+            .map(|cookie| cookie.value().to_owned())
+        );
+
+    let Some(token) = token else {
+        return Ok(
+            HttpResponse::Ok()
+                .json(IntrospectResponse {
+                    reason: Some("missing".to_string()),
+                    ..Default::default()
+                })
+        );
+    };
+
+    let response = match decode_token(&context.get_db_connection(), &token, context.jwt_keys()).await? {
+        Ok(claims) => IntrospectResponse {
+            active: true,
+            email: Some(claims.email().to_string()),
+            exp: Some(claims.exp()),
+            seconds_remaining: Some(
+                i64::try_from(claims.exp()).unwrap_or(i64::MAX) - Utc::now().timestamp()
+            ),
+            reason: None
+        },
+
+        Err(rejection) => IntrospectResponse {
+            reason: Some(rejection.to_string()),
+            ..Default::default()
+        }
+    };
+
+    Ok(
+        HttpResponse::Ok()
+            .json(response)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::App;
+    use base64::prelude::BASE64_STANDARD;
+    use base64::Engine;
+
+    use super::*;
+    use crate::models::accounts::Account;
+    use crate::utils::application::jwt::JwtKeys;
+    use crate::utils::extractors::authentication::issue_token;
+    use crate::utils::testing::{test_config, test_pool, TEST_PASSWORD};
+
+    // The cookie must not outlive the token it holds, nor
+    // expire before it.
+    #[test]
+    fn cookie_expires_with_the_token() {
+        let config = test_config(&[]);
+        let jwt_keys = JwtKeys::from_config(&config).unwrap();
+
+        let (token, claims) = issue_token(config.admin_email(), &jwt_keys).unwrap();
+        let token_lifetime = i64::try_from(claims.exp()).unwrap() - Utc::now().timestamp();
+
+        let cookie_lifetime = authentication_cookie(&config, token)
+            .max_age()
+            .unwrap()
+            .whole_seconds();
+
+        assert!((cookie_lifetime - token_lifetime).abs() <= 1);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+    async fn login_sets_a_cookie_the_scope_accepts() {
+        let context = AppContext::for_test(test_pool().await);
+        Account::upsert_from_config(&context.get_db_connection(), context.config())
+            .await
+            .unwrap();
+
+        let credentials = BASE64_STANDARD
+            .encode(format!("{}:{TEST_PASSWORD}", context.config().admin_email()));
+        let app = init_service(
+            App::new()
+                .app_data(Data::new(context))
+                .service(authentication_scope())
+        )
+            .await;
+
+        let response = call_service(
+            &app,
+            TestRequest::post()
+                .uri("/authentication/login")
+                .insert_header(("Authorization", format!("Basic {credentials}")))
+                .to_request()
+        )
+            .await;
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let cookie = response
+            .response()
+            .cookies()
+            .find(|cookie| cookie.name() == COOKIE_KEY)
+            .unwrap()
+            .into_owned();
+
+        let response = call_service(
+            &app,
+            TestRequest::get()
+                .uri("/authentication/me")
+                .cookie(cookie)
+                .to_request()
+        )
+            .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+    async fn scope_rejects_missing_credentials() {
+        let app = init_service(
+            App::new()
+                .app_data(Data::new(AppContext::for_test(test_pool().await)))
+                .service(authentication_scope())
+        )
+            .await;
+
+        for request in [
+            TestRequest::post().uri("/authentication/login"),
+            TestRequest::post().uri("/authentication/logout"),
+            TestRequest::get().uri("/authentication/me")
+        ] {
+            let response = call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+}
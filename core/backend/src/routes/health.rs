@@ -0,0 +1,73 @@
+use actix_failwrap::proof_route;
+use actix_web::web::Data;
+use actix_web::HttpResponse;
+use actix_web::http::StatusCode;
+use serde::Serialize;
+use thiserror::Error;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::utils::application::context::AppContext;
+use crate::utils::application::errors::{http_error, ErrorBody, ErrorCode, HttpError};
+
+/// Holds errors related to the readiness probe trough HTTP.
+#[derive(Error, Debug)]
+enum HealthRequestError {
+    #[error("The database is unreachable.")]
+    DatabaseUnavailable
+}
+
+impl HttpError for HealthRequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::DatabaseUnavailable => StatusCode::SERVICE_UNAVAILABLE
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::DatabaseUnavailable => ErrorCode::DatabaseUnavailable
+        }
+    }
+}
+
+http_error!(HealthRequestError);
+
+/// The OpenAPI specification for this module.
+#[derive(OpenApi)]
+#[openapi(paths(readiness_route))]
+pub struct HealthApi;
+
+/// The readiness state, only sent when ready.
+#[derive(Serialize, ToSchema, Debug)]
+struct ReadinessResponse {
+    ready: bool
+}
+
+/// Reports whether the service can handle requests,
+/// meant for orchestrator readiness probes, so this
+/// is not authenticated.
+///
+/// The database state comes from the background
+/// health check, so probing this doesn't query.
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "The service is ready.", body = ReadinessResponse),
+        (status = 503, description = "The database is unreachable.", body = ErrorBody)
+    )
+)]
+#[proof_route("GET /ready")]
+pub async fn readiness_route(
+    context: Data<AppContext>
+) -> Result<HttpResponse, HealthRequestError> {
+    if !context.database_health().is_healthy() {
+        return Err(HealthRequestError::DatabaseUnavailable);
+    }
+
+    Ok(
+        HttpResponse::Ok()
+            .json(ReadinessResponse { ready: true })
+    )
+}
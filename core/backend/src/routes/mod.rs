@@ -1,2 +1,10 @@
 
+pub mod accounts;
+pub mod analytics;
 pub mod authentication;
+pub mod health;
+pub mod openapi;
+pub mod profiles;
+pub mod runs;
+pub mod schedule;
+pub mod status;
@@ -0,0 +1,71 @@
+use utoipa::OpenApi;
+use utoipa::openapi::OpenApi as OpenApiSpec;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::routes::accounts::AccountsApi;
+use crate::routes::analytics::AnalyticsApi;
+use crate::routes::authentication::AuthenticationApi;
+use crate::routes::health::HealthApi;
+use crate::routes::profiles::ProfilesApi;
+use crate::routes::runs::RunsApi;
+use crate::routes::schedule::ScheduleApi;
+use crate::routes::status::StatusApi;
+
+/// The root OpenAPI specification, every route
+/// module specification is merged into this.
+#[derive(OpenApi)]
+#[openapi(info(title = "reddyt", description = "The reddyt HTTP API."))]
+struct ApiDoc;
+
+/// Builds the OpenAPI specification from the route
+/// modules annotations, so it can't drift from the
+/// actual handlers.
+///
+/// Internal endpoints, meant for operating the
+/// instance rather than for clients, are only
+/// included if `include_internal` is set.
+pub fn openapi_spec(include_internal: bool) -> OpenApiSpec {
+    let mut spec = ApiDoc::openapi()
+        .merge_from(AuthenticationApi::openapi())
+        .merge_from(HealthApi::openapi())
+        .merge_from(ProfilesApi::openapi())
+        .merge_from(RunsApi::openapi())
+        .merge_from(ScheduleApi::openapi())
+        .merge_from(AnalyticsApi::openapi())
+        .merge_from(AccountsApi::openapi());
+
+    if include_internal {
+        spec.merge(StatusApi::openapi());
+    }
+
+    spec
+}
+
+/// The exported service for this module, it serves
+/// the specification at `/openapi.json` and a
+/// Swagger UI for it at `/docs`.
+pub fn openapi_service(spec: OpenApiSpec) -> SwaggerUi {
+    SwaggerUi::new("/docs/{_:.*}")
+        .url("/openapi.json", spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_routes_are_documented_by_default() {
+        let paths = openapi_spec(false).paths.paths;
+
+        for path in ["/profiles/{id}", "/runs/{id}/usage", "/schedule/upcoming", "/analytics", "/accounts"] {
+            assert!(paths.contains_key(path), "{path} is missing from the default spec");
+        }
+
+        assert!(!paths.contains_key("/status"));
+    }
+
+    #[test]
+    fn internal_routes_are_documented_on_demand() {
+        assert!(openapi_spec(true).paths.paths.contains_key("/status"));
+    }
+}
@@ -0,0 +1,299 @@
+use actix_failwrap::proof_route;
+use actix_web::web::{scope, Data, Json, Path};
+use actix_web::{HttpResponse, Scope};
+use actix_web::http::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::models::profile_stages::{ProfileStage, ProfileStageError};
+use crate::models::profiles::{Profile, ProfileError, ProfileStats, ProfileUpdate};
+use crate::models::runs::{Run, RunError};
+use crate::utils::application::context::AppContext;
+use crate::utils::application::errors::{http_error, ErrorBody, ErrorCode, HttpError};
+use crate::utils::extractors::authentication::OptionalAuth;
+use crate::utils::extractors::list::ListParams;
+
+/// Holds errors related to profiles trough HTTP.
+#[derive(Error, Debug)]
+enum ProfilesRequestError {
+    #[error("Invalid or not provided credentials.")]
+    Unauthorized,
+
+    #[error("The profile doesn't exist.")]
+    NotFound,
+
+    #[error("The stage doesn't exist in this profile.")]
+    StageNotFound,
+
+    #[error("The timezone \"{0}\" is not a valid IANA timezone.")]
+    InvalidTimezone(String),
+
+    #[error("The schedule is not a valid cron expression, {0}")]
+    InvalidSchedule(String),
+
+    #[error("The daily run cap can't be negative.")]
+    InvalidDailyRunCap,
+
+    #[error("The schedule jitter window can't be negative.")]
+    InvalidScheduleJitter,
+
+    #[error("The locale \"{0}\" is not a valid BCP-47 language tag.")]
+    InvalidLocale(String),
+
+    #[error("The profile was modified since it was read, reload it and retry.")]
+    VersionConflict,
+
+    #[error("Couldn't query the profile, {0:#}")]
+    Profile(#[from] ProfileError),
+
+    #[error("Couldn't query the profile runs, {0:#}")]
+    Run(#[from] RunError),
+
+    #[error("Couldn't query the profile stage, {0:#}")]
+    Stage(#[from] ProfileStageError)
+}
+
+impl HttpError for ProfilesRequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::NotFound | Self::StageNotFound => StatusCode::NOT_FOUND,
+            Self::InvalidTimezone(_)
+                | Self::InvalidSchedule(_)
+                | Self::InvalidDailyRunCap
+                | Self::InvalidScheduleJitter
+                | Self::InvalidLocale(_) => StatusCode::BAD_REQUEST,
+            Self::VersionConflict => StatusCode::CONFLICT,
+            Self::Profile(_)
+                | Self::Run(_)
+                | Self::Stage(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::NotFound => ErrorCode::ProfileNotFound,
+            Self::StageNotFound => ErrorCode::StageNotFound,
+            Self::InvalidTimezone(_) => ErrorCode::InvalidTimezone,
+            Self::InvalidSchedule(_) => ErrorCode::InvalidSchedule,
+            Self::InvalidDailyRunCap => ErrorCode::InvalidDailyRunCap,
+            Self::InvalidScheduleJitter => ErrorCode::InvalidScheduleJitter,
+            Self::InvalidLocale(_) => ErrorCode::InvalidLocale,
+            Self::VersionConflict => ErrorCode::ProfileVersionConflict,
+            Self::Profile(_)
+                | Self::Run(_)
+                | Self::Stage(_) => ErrorCode::Database
+        }
+    }
+}
+
+http_error!(ProfilesRequestError);
+
+/// The OpenAPI specification for this module.
+#[derive(OpenApi)]
+#[openapi(paths(update_profile_route, profile_stats_route, profile_runs_route, stage_enabled_route))]
+pub struct ProfilesApi;
+
+/// Whether a stage should be composed.
+#[derive(Deserialize, ToSchema, Debug)]
+struct StageEnabledRequest {
+    enabled: bool
+}
+
+/// The exported scope for this module,
+/// it contains the profile management routes.
+pub fn profiles_scope() -> Scope {
+    scope("/profiles")
+        .service(update_profile_route)
+        .service(profile_stats_route)
+        .service(profile_runs_route)
+        .service(stage_enabled_route)
+}
+
+/// Replaces the editable settings of a profile, the body
+/// must carry the `version` the client read, if the
+/// profile was modified since then 409 is returned
+/// and nothing is written.
+#[utoipa::path(
+    put,
+    path = "/profiles/{id}",
+    tag = "profiles",
+    params(("id" = i32, Path, description = "The profile primary key.")),
+    request_body = ProfileUpdate,
+    responses(
+        (status = 200, description = "The updated profile.", body = Profile),
+        (status = 400, description = "The settings are not valid.", body = ErrorBody),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 404, description = "The profile doesn't exist.", body = ErrorBody),
+        (status = 409, description = "The profile was modified since it was read.", body = ErrorBody),
+        (status = 500, description = "The profile couldn't be updated.", body = ErrorBody)
+    )
+)]
+#[proof_route("PUT /{id}")]
+async fn update_profile_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    id: Path<i32>,
+    body: Json<ProfileUpdate>
+) -> Result<HttpResponse, ProfilesRequestError> {
+    if !auth.is_authenticated() {
+        return Err(ProfilesRequestError::Unauthorized);
+    }
+
+    let connection = context.get_db_connection();
+
+    let mut profile = Profile::get_by_id(&connection, id.into_inner())
+        .await?
+        .ok_or(ProfilesRequestError::NotFound)?;
+
+    match profile.update(&connection, body.into_inner()).await {
+        Ok(()) => Ok(
+            HttpResponse::Ok()
+                .json(profile)
+        ),
+
+        Err(ProfileError::VersionConflict) => Err(ProfilesRequestError::VersionConflict),
+
+        Err(ProfileError::InvalidTimezone(timezone)) => Err(ProfilesRequestError::InvalidTimezone(timezone)),
+
+        Err(ProfileError::InvalidSchedule(error)) => Err(ProfilesRequestError::InvalidSchedule(error.to_string())),
+
+        Err(ProfileError::NegativeDailyRunCap) => Err(ProfilesRequestError::InvalidDailyRunCap),
+
+        Err(ProfileError::NegativeScheduleJitter) => Err(ProfilesRequestError::InvalidScheduleJitter),
+
+        Err(ProfileError::InvalidLocale(locale)) => Err(ProfilesRequestError::InvalidLocale(locale)),
+
+        Err(error) => Err(error.into())
+    }
+}
+
+/// Returns the aggregated run statistics of a
+/// profile, a profile without runs is not an
+/// error, see `ProfileStats`.
+#[utoipa::path(
+    get,
+    path = "/profiles/{id}/stats",
+    tag = "profiles",
+    params(("id" = i32, Path, description = "The profile primary key.")),
+    responses(
+        (status = 200, description = "The profile statistics.", body = ProfileStats),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 404, description = "The profile doesn't exist.", body = ErrorBody),
+        (status = 500, description = "The statistics couldn't be queried.", body = ErrorBody)
+    )
+)]
+#[proof_route("GET /{id}/stats")]
+async fn profile_stats_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    id: Path<i32>
+) -> Result<HttpResponse, ProfilesRequestError> {
+    if !auth.is_authenticated() {
+        return Err(ProfilesRequestError::Unauthorized);
+    }
+
+    let connection = context.get_db_connection();
+
+    let profile = Profile::get_by_id(&connection, id.into_inner())
+        .await?
+        .ok_or(ProfilesRequestError::NotFound)?;
+
+    Ok(
+        HttpResponse::Ok()
+            .json(profile.stats(&connection).await?)
+    )
+}
+
+/// Returns the run history of a profile newest first,
+/// paginated with the `ListParams` query parameters,
+/// the `before` cursor is the last received run id.
+#[utoipa::path(
+    get,
+    path = "/profiles/{id}/runs",
+    tag = "profiles",
+    params(
+        ("id" = i32, Path, description = "The profile primary key."),
+        ("limit" = Option<i64>, Query, description = "How many runs to return, 20 by default."),
+        ("offset" = Option<i64>, Query, description = "How many runs to skip."),
+        ("before" = Option<i32>, Query, description = "Only runs with a lower id are returned.")
+    ),
+    responses(
+        (status = 200, description = "The profile runs, newest first.", body = Vec<Run>),
+        (status = 400, description = "Invalid query parameters.", body = ErrorBody),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 404, description = "The profile doesn't exist.", body = ErrorBody),
+        (status = 500, description = "The runs couldn't be queried.", body = ErrorBody)
+    )
+)]
+#[proof_route("GET /{id}/runs")]
+async fn profile_runs_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    id: Path<i32>,
+    params: ListParams
+) -> Result<HttpResponse, ProfilesRequestError> {
+    if !auth.is_authenticated() {
+        return Err(ProfilesRequestError::Unauthorized);
+    }
+
+    let connection = context.get_db_connection();
+
+    let profile = Profile::get_by_id(&connection, id.into_inner())
+        .await?
+        .ok_or(ProfilesRequestError::NotFound)?;
+
+    Ok(
+        HttpResponse::Ok()
+            .json(Run::list_by_profile(&connection, profile.id(), &params).await?)
+    )
+}
+
+/// Enables or disables a profile stage without removing
+/// it from the graph, a disabled stage is skipped while
+/// the stages after it still run.
+#[utoipa::path(
+    put,
+    path = "/profiles/{id}/stages/{stage_id}/enabled",
+    tag = "profiles",
+    params(
+        ("id" = i32, Path, description = "The profile primary key."),
+        ("stage_id" = i32, Path, description = "The stage primary key.")
+    ),
+    request_body = StageEnabledRequest,
+    responses(
+        (status = 200, description = "The updated stage.", body = ProfileStage),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 404, description = "The stage doesn't exist in this profile.", body = ErrorBody),
+        (status = 500, description = "The stage couldn't be updated.", body = ErrorBody)
+    )
+)]
+#[proof_route("PUT /{id}/stages/{stage_id}/enabled")]
+async fn stage_enabled_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    path: Path<(i32, i32)>,
+    body: Json<StageEnabledRequest>
+) -> Result<HttpResponse, ProfilesRequestError> {
+    if !auth.is_authenticated() {
+        return Err(ProfilesRequestError::Unauthorized);
+    }
+
+    let connection = context.get_db_connection();
+    let (profile_id, stage_id) = path.into_inner();
+
+    let mut stage = ProfileStage::get_by_id(&connection, profile_id, stage_id)
+        .await?
+        .ok_or(ProfilesRequestError::StageNotFound)?;
+
+    stage
+        .set_enabled(&connection, body.enabled)
+        .await?;
+
+    Ok(
+        HttpResponse::Ok()
+            .json(stage)
+    )
+}
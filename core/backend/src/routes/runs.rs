@@ -0,0 +1,91 @@
+use actix_failwrap::proof_route;
+use actix_web::web::{scope, Data, Path};
+use actix_web::{HttpResponse, Scope};
+use actix_web::http::StatusCode;
+use thiserror::Error;
+use utoipa::OpenApi;
+
+use crate::models::run_usage::{RunUsage, RunUsageError};
+use crate::utils::application::context::AppContext;
+use crate::utils::application::errors::{http_error, ErrorBody, ErrorCode, HttpError};
+use crate::utils::extractors::authentication::OptionalAuth;
+
+/// Holds errors related to runs trough HTTP.
+#[derive(Error, Debug)]
+enum RunsRequestError {
+    #[error("Invalid or not provided credentials.")]
+    Unauthorized,
+
+    #[error("The run doesn't exist or didn't report usage yet.")]
+    UsageNotFound,
+
+    #[error("Couldn't query the run usage, {0:#}")]
+    RunUsage(#[from] RunUsageError)
+}
+
+impl HttpError for RunsRequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::UsageNotFound => StatusCode::NOT_FOUND,
+            Self::RunUsage(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::UsageNotFound => ErrorCode::RunUsageNotFound,
+            Self::RunUsage(_) => ErrorCode::Database
+        }
+    }
+}
+
+http_error!(RunsRequestError);
+
+/// The OpenAPI specification for this module.
+#[derive(OpenApi)]
+#[openapi(paths(run_usage_route))]
+pub struct RunsApi;
+
+/// The exported scope for this module,
+/// it contains the run history routes.
+pub fn runs_scope() -> Scope {
+    scope("/runs")
+        .service(run_usage_route)
+}
+
+/// Returns what a run consumed from external providers
+/// and compute, figures may be estimates if a provider
+/// doesn't report usage, see `estimated`.
+#[utoipa::path(
+    get,
+    path = "/runs/{id}/usage",
+    tag = "runs",
+    params(("id" = i32, Path, description = "The run primary key.")),
+    responses(
+        (status = 200, description = "The run usage.", body = RunUsage),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 404, description = "The run doesn't exist or didn't report usage yet.", body = ErrorBody),
+        (status = 500, description = "The usage couldn't be queried.", body = ErrorBody)
+    )
+)]
+#[proof_route("GET /{id}/usage")]
+async fn run_usage_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    id: Path<i32>
+) -> Result<HttpResponse, RunsRequestError> {
+    if !auth.is_authenticated() {
+        return Err(RunsRequestError::Unauthorized);
+    }
+
+    let usage = RunUsage::get_by_run(&context.get_db_connection(), id.into_inner())
+        .await?
+        .ok_or(RunsRequestError::UsageNotFound)?;
+
+    Ok(
+        HttpResponse::Ok()
+            .json(usage)
+    )
+}
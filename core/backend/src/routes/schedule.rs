@@ -0,0 +1,235 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use actix_failwrap::proof_route;
+use actix_web::web::{Data, Query};
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::models::profiles::{Profile, ProfileError};
+use crate::utils::application::context::AppContext;
+use crate::utils::application::errors::{http_error, ErrorBody, ErrorCode, HttpError};
+use crate::utils::extractors::authentication::OptionalAuth;
+
+/// How many upcoming runs are returned
+/// when `count` is not provided.
+const DEFAULT_UPCOMING_COUNT: usize = 10;
+/// The maximum amount of upcoming runs
+/// a single request may return.
+const MAX_UPCOMING_COUNT: usize = 100;
+
+/// Holds errors related to the schedule trough HTTP.
+#[derive(Error, Debug)]
+enum ScheduleRequestError {
+    #[error("Invalid or not provided credentials.")]
+    Unauthorized,
+
+    #[error("Invalid query parameters, {0}")]
+    InvalidQuery(String),
+
+    #[error("The count must be between 1 and {MAX_UPCOMING_COUNT}.")]
+    CountOutOfBounds,
+
+    #[error("Couldn't query the profiles, {0:#}")]
+    Profile(#[from] ProfileError)
+}
+
+impl HttpError for ScheduleRequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::InvalidQuery(_) | Self::CountOutOfBounds => StatusCode::BAD_REQUEST,
+            Self::Profile(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::InvalidQuery(_) => ErrorCode::InvalidQuery,
+            Self::CountOutOfBounds => ErrorCode::CountOutOfBounds,
+            Self::Profile(_) => ErrorCode::Database
+        }
+    }
+}
+
+http_error!(ScheduleRequestError);
+
+/// The OpenAPI specification for this module.
+#[derive(OpenApi)]
+#[openapi(paths(upcoming_route))]
+pub struct ScheduleApi;
+
+/// The upcoming query parameters.
+#[derive(Deserialize, Debug)]
+struct UpcomingQuery {
+    count: Option<usize>
+}
+
+/// The next scheduled run of a profile.
+#[derive(Serialize, ToSchema, Debug, PartialEq, Eq)]
+struct UpcomingRun {
+    runs_at: DateTime<Utc>,
+    priority: i32,
+    profile_id: i32,
+    profile_name: String
+}
+
+impl Ord for UpcomingRun {
+    /// Soonest first, the highest priority
+    /// first on ties, then by profile id.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.runs_at, Reverse(self.priority), self.profile_id)
+            .cmp(&(other.runs_at, Reverse(other.priority), other.profile_id))
+    }
+}
+
+impl PartialOrd for UpcomingRun {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Returns the soonest `count` scheduled runs across
+/// every profile, sorted by `runs_at` ascending, then
+/// by priority descending and by profile id on ties.
+///
+/// Only the next run of each profile is considered,
+/// paused profiles are excluded, as are profiles
+/// whose schedule or timezone doesn't parse, which is logged, or
+/// never fires, which is flagged by the profile schedule warning.
+///
+/// A profile that reached its daily run cap is only excluded
+/// when its next run is due today, since the cap resets at the
+/// profile midnight. The runs started today are counted for
+/// every capped profile with a single query.
+///
+/// This is registered as a service on its own since
+/// an empty scope would shadow any later service.
+#[utoipa::path(
+    get,
+    path = "/schedule/upcoming",
+    tag = "schedule",
+    params(("count" = Option<usize>, Query, description = "How many runs to return, 10 by default, at most 100.")),
+    responses(
+        (status = 200, description = "The upcoming runs, soonest first.", body = Vec<UpcomingRun>),
+        (status = 400, description = "Invalid query parameters.", body = ErrorBody),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 500, description = "The profiles couldn't be queried.", body = ErrorBody)
+    )
+)]
+#[proof_route("GET /schedule/upcoming")]
+pub async fn upcoming_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>,
+    request: HttpRequest
+) -> Result<HttpResponse, ScheduleRequestError> {
+    if !auth.is_authenticated() {
+        return Err(ScheduleRequestError::Unauthorized);
+    }
+
+    let Query(query) = Query::<UpcomingQuery>::from_query(request.query_string())
+        .map_err(|error| ScheduleRequestError::InvalidQuery(error.to_string()))?;
+
+    let count = query.count.unwrap_or(DEFAULT_UPCOMING_COUNT);
+    if !(1..=MAX_UPCOMING_COUNT).contains(&count) {
+        return Err(ScheduleRequestError::CountOutOfBounds);
+    }
+
+    let connection = context.get_db_connection();
+    let now = Utc::now();
+
+    // A max-heap bounded to `count`, the latest run is
+    // evicted on overflow so only the soonest remain.
+    let mut upcoming = BinaryHeap::with_capacity(count + 1);
+
+    let profiles = Profile::list_unpaused(&connection).await?;
+    let runs_today = Profile::runs_today(&connection, &profiles, now).await?;
+
+    for profile in profiles {
+        let runs_at = match profile.next_run(now) {
+            Ok(runs_at) => runs_at,
+
+            // Already surfaced trough the schedule warning
+            // when the profile was updated.
+            Err(ProfileError::ScheduleNeverFires) => continue,
+
+            Err(error) => {
+                log::warn!("Skipping profile {} in the upcoming schedule, {error:#}", profile.id());
+                continue;
+            }
+        };
+
+        // The timezone was already validated by `next_run`.
+        if profile.cap_excludes(runs_at, now, &runs_today)? {
+            continue;
+        }
+
+        upcoming.push(UpcomingRun {
+            runs_at,
+            priority: profile.priority(),
+            profile_id: profile.id(),
+            profile_name: profile.name().to_string()
+        });
+
+        if upcoming.len() > count {
+            upcoming.pop();
+        }
+    }
+
+    Ok(
+        HttpResponse::Ok()
+            .json(upcoming.into_sorted_vec())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    fn upcoming(runs_at: DateTime<Utc>, priority: i32, profile_id: i32) -> UpcomingRun {
+        UpcomingRun {
+            runs_at,
+            priority,
+            profile_id,
+            profile_name: format!("profile-{profile_id}")
+        }
+    }
+
+    #[test]
+    fn higher_priority_runs_first_when_due_together() {
+        let now = Utc::now();
+
+        let mut runs = [
+            upcoming(now, 0, 1),
+            upcoming(now, 10, 2),
+            upcoming(now - TimeDelta::minutes(1), -5, 3),
+            upcoming(now, 10, 4)
+        ];
+        runs.sort();
+
+        let order = runs.iter().map(|run| run.profile_id).collect::<Vec<_>>();
+        assert_eq!(order, [3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn the_bounded_heap_keeps_the_higher_priority() {
+        let now = Utc::now();
+        let mut heap = BinaryHeap::new();
+
+        for run in [upcoming(now, 0, 1), upcoming(now, 5, 2)] {
+            heap.push(run);
+
+            if heap.len() > 1 {
+                heap.pop();
+            }
+        }
+
+        assert_eq!(heap.into_sorted_vec(), [upcoming(now, 5, 2)]);
+    }
+}
@@ -0,0 +1,175 @@
+use actix_failwrap::proof_route;
+use actix_web::web::Data;
+use actix_web::HttpResponse;
+use actix_web::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::models::profiles::{Profile, ProfileError};
+use crate::models::runs::{Run, RunError, RunFailure};
+use crate::utils::application::context::AppContext;
+use crate::utils::application::errors::{http_error, ErrorBody, ErrorCode, HttpError};
+use crate::utils::extractors::authentication::OptionalAuth;
+
+/// How many errored runs are reported by the status.
+const RECENT_FAILURES_LIMIT: i64 = 5;
+
+/// Holds errors related to the status overview trough HTTP.
+#[derive(Error, Debug)]
+enum StatusRequestError {
+    #[error("Invalid or not provided credentials.")]
+    Unauthorized,
+
+    #[error("Couldn't obtain the profiles status, {0:#}")]
+    Profile(#[from] ProfileError),
+
+    #[error("Couldn't obtain the runs status, {0:#}")]
+    Run(#[from] RunError)
+}
+
+impl HttpError for StatusRequestError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Profile(_) | Self::Run(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::Profile(_) | Self::Run(_) => ErrorCode::Database
+        }
+    }
+}
+
+http_error!(StatusRequestError);
+
+/// The OpenAPI specification for this module.
+#[derive(OpenApi)]
+#[openapi(paths(status_route))]
+pub struct StatusApi;
+
+/// The runs a capped profile may still start today.
+#[derive(Serialize, ToSchema, Debug)]
+struct RemainingDailyRuns {
+    profile_id: i32,
+    remaining: i64
+}
+
+/// The aggregated system state, meant to be
+/// polled by the dashboard header.
+#[derive(Serialize, ToSchema, Debug)]
+struct StatusResponse {
+    active_runs: i64,
+    total_profiles: i64,
+    paused_profiles: i64,
+    recent_failures: Vec<RunFailure>,
+    remaining_daily_runs: Vec<RemainingDailyRuns>
+}
+
+/// The runs each unpaused profile with a daily
+/// run cap may still start today, at `now`.
+async fn remaining_daily_runs(
+    connection: &PgPool,
+    now: DateTime<Utc>
+) -> Result<Vec<RemainingDailyRuns>, ProfileError> {
+    let profiles = Profile::list_unpaused(connection).await?;
+    let runs_today = Profile::runs_today(connection, &profiles, now).await?;
+
+    Ok(
+        profiles
+            .iter()
+            .filter_map(|profile| Some(RemainingDailyRuns {
+                profile_id: profile.id(),
+                remaining: runs_today.remaining(profile)?
+            }))
+            .collect()
+    )
+}
+
+/// Aggregates the system state in a single response,
+/// COUNT queries, a bounded failure list and a single
+/// grouped query for the daily run caps are performed
+/// to keep this cheap to poll.
+///
+/// This is registered as a service on its own since
+/// an empty scope would shadow any later service.
+#[utoipa::path(
+    get,
+    path = "/status",
+    tag = "status",
+    responses(
+        (status = 200, description = "The aggregated system state.", body = StatusResponse),
+        (status = 401, description = "Invalid or not provided credentials.", body = ErrorBody),
+        (status = 500, description = "The state couldn't be queried.", body = ErrorBody)
+    )
+)]
+#[proof_route("GET /status")]
+pub async fn status_route(
+    auth: OptionalAuth,
+    context: Data<AppContext>
+) -> Result<HttpResponse, StatusRequestError> {
+    if !auth.is_authenticated() {
+        return Err(StatusRequestError::Unauthorized);
+    }
+
+    let connection = context.get_db_connection();
+
+    let (total_profiles, paused_profiles) = Profile::count(&connection)
+        .await?;
+
+    Ok(
+        HttpResponse::Ok()
+            .json(StatusResponse {
+                active_runs: Run::count_active(&connection).await?,
+                total_profiles,
+                paused_profiles,
+                recent_failures: Run::recent_failures(&connection, RECENT_FAILURES_LIMIT)
+                    .await?,
+                remaining_daily_runs: remaining_daily_runs(&connection, Utc::now())
+                    .await?
+            })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::query;
+
+    use super::*;
+    use crate::utils::testing::{insert_profile, insert_run, test_pool};
+
+    #[actix_web::test]
+    #[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+    async fn only_capped_profiles_report_remaining_runs() {
+        let connection = test_pool().await;
+        let capped = insert_profile(&connection, "0 * * * *").await;
+        let uncapped = insert_profile(&connection, "0 * * * *").await;
+
+        query("UPDATE profiles SET daily_run_cap = 3 WHERE id = $1")
+            .bind(capped.id())
+            .execute(&connection)
+            .await
+            .unwrap();
+
+        insert_run(&connection, capped.id(), true, None).await;
+        insert_run(&connection, capped.id(), true, Some("failed")).await;
+
+        let remaining = remaining_daily_runs(&connection, Utc::now())
+            .await
+            .unwrap();
+
+        let remaining_of = |profile: &Profile| remaining
+            .iter()
+            .find(|remaining| remaining.profile_id == profile.id())
+            .map(|remaining| remaining.remaining);
+
+        // Errored runs don't count towards the cap.
+        assert_eq!(remaining_of(&capped), Some(2));
+        assert_eq!(remaining_of(&uncapped), None);
+    }
+}
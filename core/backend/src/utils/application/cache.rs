@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A shared in-memory cache whose entries expire
+/// after a fixed time to live.
+///
+/// Expired entries are replaced on insertion, this
+/// is meant for a small and bounded set of keys.
+#[derive(Debug, Clone)]
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<K, (Instant, V)>>>
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    /// Creates an empty cache, a zero `ttl`
+    /// disables caching entirely.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new()))
+        }
+    }
+
+    /// Obtains a copy of the entry for `key`
+    /// if it exists and didn't expire.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entries = self.entries
+            .lock()
+            .ok()?;
+
+        entries
+            .get(key)
+            .filter(|(inserted_at, _)| inserted_at.elapsed() < self.ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Stores a value for `key`, replacing any
+    /// previous entry and resetting its expiration.
+    pub fn insert(&self, key: K, value: V) {
+        if self.ttl.is_zero() {
+            return;
+        }
+
+        // A poisoned lock only means a panic while
+        // inserting, the cache is still usable.
+        let mut entries = self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        entries.insert(key, (Instant::now(), value));
+    }
+}
@@ -1,10 +1,23 @@
 use std::sync::Arc;
 
+use reqwest::{Client, Error as ReqwestError};
 use sqlx::{Pool, Postgres};
 use thiserror::Error;
 
+use crate::models::accounts::{Account, AccountError};
+use crate::models::runs::RunAnalytics;
+use crate::utils::application::cache::TtlCache;
 use crate::utils::application::environment::{ReddytConfig, ReddytConfigError};
-use crate::utils::external::database::{init_db_connection, DbConnectionError};
+use crate::utils::application::jwt::{JwtKeys, JwtKeysError};
+use crate::utils::application::notifications::Notifier;
+use crate::utils::external::database::{
+    init_db_connection,
+    set_slow_query_threshold,
+    DatabaseHealth,
+    DbConnectionError
+};
+use crate::utils::external::email::EmailError;
+use crate::utils::external::http::build_http_client;
 
 /// Holds any errors related to the application context
 /// i.e database connections, environment...
@@ -15,6 +28,18 @@ pub enum AppContextError {
 
     #[error("Error while connecting to the Database, {0:#}")]
     DataBase(#[from] DbConnectionError),
+
+    #[error("Error while configuring email notifications, {0:#}")]
+    Email(#[from] EmailError),
+
+    #[error("Error while loading the JWT keys, {0:#}")]
+    JwtKeys(#[from] JwtKeysError),
+
+    #[error("Error while building the HTTP client, {0:#}")]
+    Http(#[from] ReqwestError),
+
+    #[error("Error while syncing the admin account, {0:#}")]
+    AdminAccount(#[from] AccountError),
 }
 
 /// The application context, registered as data in the
@@ -26,7 +51,12 @@ pub enum AppContextError {
 #[derive(Clone, Debug)]
 pub struct AppContext {
     config: Arc<ReddytConfig>,
-    connection_pool: Arc<Pool<Postgres>>
+    connection_pool: Arc<Pool<Postgres>>,
+    database_health: DatabaseHealth,
+    analytics_cache: TtlCache<i32, RunAnalytics>,
+    http_client: Client,
+    notifier: Notifier,
+    jwt_keys: JwtKeys
 }
 
 impl AppContext {
@@ -34,15 +64,67 @@ impl AppContext {
     /// defaults.
     pub async fn new() -> Result<Self, AppContextError> {
         let config = ReddytConfig::load_validated()?;
-        let connection_pool = init_db_connection(config.database_url())
+        set_slow_query_threshold(config.slow_query_threshold());
+
+        let connection_pool = Arc::new(
+            init_db_connection(config.database_url().expose())
+                .await?
+        );
+
+        // The configured admin lives in the accounts
+        // table too, kept in sync with the environment.
+        Account::upsert_from_config(&connection_pool, &config)
             .await?;
 
+        let database_health = DatabaseHealth::spawn(
+            connection_pool.clone(),
+            config.db_health_interval()
+        );
+
+        let analytics_cache = TtlCache::new(config.analytics_ttl());
+        let http_client = build_http_client(&config)?;
+        let notifier = Notifier::from_config(&config, &http_client)?;
+        let jwt_keys = JwtKeys::from_config(&config)?;
+
         Ok(Self {
             config: Arc::new(config),
-            connection_pool: Arc::new(connection_pool)
+            connection_pool,
+            database_health,
+            analytics_cache,
+            http_client,
+            notifier,
+            jwt_keys
         })
     }
 
+    /// A context over `connection_pool` configured by
+    /// `test_config`, for serving routes in tests.
+    #[cfg(test)]
+    pub fn for_test(connection_pool: Pool<Postgres>) -> Self {
+        let config = crate::utils::testing::test_config(&[]);
+        let connection_pool = Arc::new(connection_pool);
+
+        let database_health = DatabaseHealth::spawn(
+            connection_pool.clone(),
+            config.db_health_interval()
+        );
+
+        let analytics_cache = TtlCache::new(config.analytics_ttl());
+        let http_client = build_http_client(&config).unwrap();
+        let notifier = Notifier::from_config(&config, &http_client).unwrap();
+        let jwt_keys = JwtKeys::from_config(&config).unwrap();
+
+        Self {
+            config: Arc::new(config),
+            connection_pool,
+            database_health,
+            analytics_cache,
+            http_client,
+            notifier,
+            jwt_keys
+        }
+    }
+
     /// The application environment configuration.
     #[inline]
     pub fn config(&self) -> &ReddytConfig {
@@ -54,4 +136,39 @@ impl AppContext {
     pub fn get_db_connection(&self) -> Arc<Pool<Postgres>> {
         self.connection_pool.clone()
     }
+
+    /// Whether the database is currently reachable.
+    #[inline]
+    pub fn database_health(&self) -> &DatabaseHealth {
+        &self.database_health
+    }
+
+    /// The computed analytics, keyed by
+    /// the amount of days they span.
+    #[inline]
+    pub fn analytics_cache(&self) -> &TtlCache<i32, RunAnalytics> {
+        &self.analytics_cache
+    }
+
+    /// The HTTP client every outbound integration
+    /// must reuse, cloning it is cheap.
+    #[inline]
+    #[allow(dead_code)]
+    pub fn http_client(&self) -> &Client {
+        &self.http_client
+    }
+
+    /// The run notification dispatcher.
+    #[inline]
+    #[allow(dead_code)]
+    pub fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+
+    /// The keys the application JWTs are
+    /// signed and verified with.
+    #[inline]
+    pub fn jwt_keys(&self) -> &JwtKeys {
+        &self.jwt_keys
+    }
 }
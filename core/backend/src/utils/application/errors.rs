@@ -1,31 +1,345 @@
-use std::collections::HashMap;
 use std::backtrace::Backtrace;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
 
-use actix_web::{HttpResponse, HttpResponseBuilder};
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::ToSchema;
 
-/// JSON error formatter for `actix_failwrap`.
+/// A stable machine readable code for error responses,
+/// SDKs should switch on this rather than on the message.
 ///
-/// This formats the errors HTTP error deriving from `actix_failwrap`
-/// that use this as a formater as JSON.
+/// **Codes are part of the public API, variants may be
+/// added but never renamed nor removed.**
+///
+/// Every HTTP error enum declares the code of each of
+/// its variants trough `HttpError::code`.
+///
+/// The current codes are
+/// - `UNAUTHORIZED`: invalid or missing credentials.
+/// - `INTERNAL`: a server side error unrelated to the request.
+/// - `DATABASE`: a database query failed.
+/// - `TOKEN_ENCODING`: the session token couldn't be issued.
+/// - `INVALID_QUERY`: the query parameters don't parse.
+/// - `LIMIT_OUT_OF_BOUNDS`: the list `limit` is out of its bounds.
+/// - `NEGATIVE_OFFSET`: the list `offset` is negative.
+/// - `COUNT_OUT_OF_BOUNDS`: the upcoming schedule `count` is out of its bounds.
+/// - `DAYS_OUT_OF_BOUNDS`: the analytics `days` is out of its bounds.
+/// - `PAYLOAD_TOO_LARGE`: the body exceeds the configured limit.
+/// - `MALFORMED_JSON`: the body is not valid JSON.
+/// - `INVALID_BODY`: the body doesn't match the expected shape.
+/// - `UNSUPPORTED_CONTENT_TYPE`: the body is not `application/json`.
+/// - `DATABASE_UNAVAILABLE`: the database is unreachable.
+/// - `PROFILE_NOT_FOUND`: the profile doesn't exist.
+/// - `STAGE_NOT_FOUND`: the stage doesn't exist in the profile.
+/// - `RUN_USAGE_NOT_FOUND`: the run doesn't exist or didn't report usage.
+/// - `INVALID_TIMEZONE`: the timezone is not a valid IANA timezone.
+/// - `INVALID_SCHEDULE`: the schedule is not a valid cron expression.
+/// - `INVALID_DAILY_RUN_CAP`: the daily run cap is negative.
+/// - `INVALID_SCHEDULE_JITTER`: the schedule jitter window is negative.
+/// - `INVALID_LOCALE`: the locale is not a valid BCP-47 language tag.
+/// - `PROFILE_VERSION_CONFLICT`: the profile was modified since the client read it.
+/// - `INVALID_EMAIL`: the email is not valid.
+/// - `ACCOUNT_ALREADY_EXISTS`: an account with the email already exists.
+/// - `WEAK_PASSWORD`: the password doesn't meet the password policy.
+#[derive(Serialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    Unauthorized,
+    Internal,
+    Database,
+    TokenEncoding,
+    InvalidQuery,
+    LimitOutOfBounds,
+    NegativeOffset,
+    CountOutOfBounds,
+    DaysOutOfBounds,
+    PayloadTooLarge,
+    MalformedJson,
+    InvalidBody,
+    UnsupportedContentType,
+    DatabaseUnavailable,
+    ProfileNotFound,
+    StageNotFound,
+    RunUsageNotFound,
+    InvalidTimezone,
+    InvalidSchedule,
+    InvalidDailyRunCap,
+    InvalidScheduleJitter,
+    InvalidLocale,
+    ProfileVersionConflict,
+    InvalidEmail,
+    AccountAlreadyExists,
+    WeakPassword
+}
+
+impl ErrorCode {
+    /// The code as sent to clients.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::Internal => "INTERNAL",
+            Self::Database => "DATABASE",
+            Self::TokenEncoding => "TOKEN_ENCODING",
+            Self::InvalidQuery => "INVALID_QUERY",
+            Self::LimitOutOfBounds => "LIMIT_OUT_OF_BOUNDS",
+            Self::NegativeOffset => "NEGATIVE_OFFSET",
+            Self::CountOutOfBounds => "COUNT_OUT_OF_BOUNDS",
+            Self::DaysOutOfBounds => "DAYS_OUT_OF_BOUNDS",
+            Self::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            Self::MalformedJson => "MALFORMED_JSON",
+            Self::InvalidBody => "INVALID_BODY",
+            Self::UnsupportedContentType => "UNSUPPORTED_CONTENT_TYPE",
+            Self::DatabaseUnavailable => "DATABASE_UNAVAILABLE",
+            Self::ProfileNotFound => "PROFILE_NOT_FOUND",
+            Self::StageNotFound => "STAGE_NOT_FOUND",
+            Self::RunUsageNotFound => "RUN_USAGE_NOT_FOUND",
+            Self::InvalidTimezone => "INVALID_TIMEZONE",
+            Self::InvalidSchedule => "INVALID_SCHEDULE",
+            Self::InvalidDailyRunCap => "INVALID_DAILY_RUN_CAP",
+            Self::InvalidScheduleJitter => "INVALID_SCHEDULE_JITTER",
+            Self::InvalidLocale => "INVALID_LOCALE",
+            Self::ProfileVersionConflict => "PROFILE_VERSION_CONFLICT",
+            Self::InvalidEmail => "INVALID_EMAIL",
+            Self::AccountAlreadyExists => "ACCOUNT_ALREADY_EXISTS",
+            Self::WeakPassword => "WEAK_PASSWORD"
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error that is responded with an `ErrorBody`.
+///
+/// Every HTTP error enum implements this and is then
+/// registered with `http_error!`, the `Display` stays
+/// a plain human readable message.
+pub trait HttpError: Error {
+    /// The status this error is responded with.
+    fn status(&self) -> StatusCode;
+
+    /// The stable machine readable code of this error.
+    fn code(&self) -> ErrorCode;
+}
+
+/// Implements `ResponseError` for an `HttpError`, so it's
+/// responded with `json_formatter`, together with the
+/// conversion into `HttpResponse` `proof_route` requires.
+macro_rules! http_error {
+    ($error:ty) => {
+        impl ::actix_web::ResponseError for $error {
+            fn status_code(&self) -> ::actix_web::http::StatusCode {
+                $crate::utils::application::errors::HttpError::status(self)
+            }
+
+            fn error_response(&self) -> ::actix_web::HttpResponse {
+                $crate::utils::application::errors::json_formatter(self)
+            }
+        }
+
+        impl ::std::convert::From<$error> for ::actix_web::HttpResponse {
+            fn from(error: $error) -> Self {
+                $crate::utils::application::errors::json_formatter(&error)
+            }
+        }
+    };
+}
+
+pub(crate) use http_error;
+
+/// The body of every error response, as
+/// produced by `json_formatter`.
+#[derive(Serialize, ToSchema, Debug)]
+pub struct ErrorBody {
+    /// The stable machine readable error code.
+    code: ErrorCode,
+
+    /// The error `Display`, meant for humans.
+    error: String,
+
+    /// Where the error was formatted,
+    /// only present in debug builds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backtrace: Option<String>
+}
+
+impl ErrorBody {
+    /// Creates the body for an error, the backtrace
+    /// is only captured if `with_backtrace` is set.
+    fn new(error: &impl HttpError, with_backtrace: bool) -> Self {
+        Self {
+            code: error.code(),
+            error: error.to_string(),
+            backtrace: with_backtrace
+                .then(|| Backtrace::capture().to_string())
+        }
+    }
+}
+
+/// Responds with an `HttpError` as JSON, using its status.
 ///
 /// The JSON structure is the folllwing
 /// ```json
 /// {
 ///     #[cfg(debug_assertions)]
 ///     "backtrace": "..."
+///     "code": "<ErrorCode>",
 ///     "error": "<_ as Display>::to_string()"
 /// }
 /// ```
-pub fn json_formatter(mut builder: HttpResponseBuilder, display: String) -> HttpResponse {
-    let mut data = HashMap::new();
-    data.insert("error", display);
+pub fn json_formatter(error: &impl HttpError) -> HttpResponse {
+    HttpResponse::build(error.status())
+        .json(ErrorBody::new(error, cfg!(debug_assertions)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use actix_web::body::to_bytes;
+    use insta::assert_json_snapshot;
+    use thiserror::Error;
+
+    use super::*;
+
+    /// A sample error as declared by routes.
+    #[derive(Error, Debug)]
+    enum SampleError {
+        #[error("The profile doesn't exist.")]
+        NotFound
+    }
+
+    impl HttpError for SampleError {
+        fn status(&self) -> StatusCode {
+            match self {
+                Self::NotFound => StatusCode::NOT_FOUND
+            }
+        }
+
+        fn code(&self) -> ErrorCode {
+            match self {
+                Self::NotFound => ErrorCode::ProfileNotFound
+            }
+        }
+    }
+
+    http_error!(SampleError);
+
+    /// Every code, adding a variant breaks the match in
+    /// `every_code_is_listed` so it's also added here.
+    const CODES: [ErrorCode; 26] = [
+        ErrorCode::Unauthorized,
+        ErrorCode::Internal,
+        ErrorCode::Database,
+        ErrorCode::TokenEncoding,
+        ErrorCode::InvalidQuery,
+        ErrorCode::LimitOutOfBounds,
+        ErrorCode::NegativeOffset,
+        ErrorCode::CountOutOfBounds,
+        ErrorCode::DaysOutOfBounds,
+        ErrorCode::PayloadTooLarge,
+        ErrorCode::MalformedJson,
+        ErrorCode::InvalidBody,
+        ErrorCode::UnsupportedContentType,
+        ErrorCode::DatabaseUnavailable,
+        ErrorCode::ProfileNotFound,
+        ErrorCode::StageNotFound,
+        ErrorCode::RunUsageNotFound,
+        ErrorCode::InvalidTimezone,
+        ErrorCode::InvalidSchedule,
+        ErrorCode::InvalidDailyRunCap,
+        ErrorCode::InvalidScheduleJitter,
+        ErrorCode::InvalidLocale,
+        ErrorCode::ProfileVersionConflict,
+        ErrorCode::InvalidEmail,
+        ErrorCode::AccountAlreadyExists,
+        ErrorCode::WeakPassword
+    ];
 
-    #[cfg(debug_assertions)]
-    {
-        let backtrace = Backtrace::capture();
-        data.insert("backtrace", backtrace.to_string());
+    #[test]
+    fn every_code_is_listed() {
+        for code in CODES {
+            match code {
+                ErrorCode::Unauthorized
+                | ErrorCode::Internal
+                | ErrorCode::Database
+                | ErrorCode::TokenEncoding
+                | ErrorCode::InvalidQuery
+                | ErrorCode::LimitOutOfBounds
+                | ErrorCode::NegativeOffset
+                | ErrorCode::CountOutOfBounds
+                | ErrorCode::DaysOutOfBounds
+                | ErrorCode::PayloadTooLarge
+                | ErrorCode::MalformedJson
+                | ErrorCode::InvalidBody
+                | ErrorCode::UnsupportedContentType
+                | ErrorCode::DatabaseUnavailable
+                | ErrorCode::ProfileNotFound
+                | ErrorCode::StageNotFound
+                | ErrorCode::RunUsageNotFound
+                | ErrorCode::InvalidTimezone
+                | ErrorCode::InvalidSchedule
+                | ErrorCode::InvalidDailyRunCap
+                | ErrorCode::InvalidScheduleJitter
+                | ErrorCode::InvalidLocale
+                | ErrorCode::ProfileVersionConflict
+                | ErrorCode::InvalidEmail
+                | ErrorCode::AccountAlreadyExists
+                | ErrorCode::WeakPassword => {}
+            }
+        }
     }
 
-    builder
-        .json(data)
+    #[test]
+    fn codes_serialize_as_they_display() {
+        for code in CODES {
+            assert_eq!(
+                serde_json::to_value(code).unwrap(),
+                code.as_str(),
+                "{code:?} serializes differently than it displays"
+            );
+        }
+    }
+
+    #[test]
+    fn codes_are_distinct() {
+        let codes = CODES
+            .iter()
+            .map(ErrorCode::as_str)
+            .collect::<HashSet<_>>();
+
+        assert_eq!(codes.len(), CODES.len());
+    }
+
+    #[test]
+    fn debug_bodies_carry_a_backtrace() {
+        assert_json_snapshot!(
+            ErrorBody::new(&SampleError::NotFound, true),
+            { ".backtrace" => "[backtrace]" }
+        );
+    }
+
+    #[test]
+    fn release_bodies_omit_the_backtrace() {
+        assert_json_snapshot!(ErrorBody::new(&SampleError::NotFound, false));
+    }
+
+    #[actix_web::test]
+    async fn responses_use_the_error_status() {
+        let response = HttpResponse::from(SampleError::NotFound);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["code"], "PROFILE_NOT_FOUND");
+        assert_eq!(body["error"], "The profile doesn't exist.");
+    }
 }
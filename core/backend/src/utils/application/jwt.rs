@@ -0,0 +1,142 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::fs::read;
+use std::io::Error as IoError;
+use std::sync::OnceLock;
+
+use jsonwebtoken::errors::Error as JwtError;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use rand::distr::{Alphanumeric, SampleString};
+use rand::rand_core::OsError as OsRngError;
+use rand::rngs::{OsRng, StdRng};
+use rand::SeedableRng;
+use thiserror::Error;
+
+use crate::utils::application::environment::ReddytConfig;
+
+/// Holds any errors related to loading the JWT keys.
+#[derive(Error, Debug)]
+pub enum JwtKeysError {
+    #[error("Couldn't generate a valid JWT secret, {0:#}")]
+    Secret(#[from] OsRngError),
+
+    #[error("Couldn't read the JWT key file, {0:#}")]
+    KeyFile(#[from] IoError),
+
+    #[error("The JWT key file is not a valid PEM key, {0:#}")]
+    InvalidKey(#[from] JwtError),
+
+    #[error("The JWT algorithm {0:?} requires both RYT_JWT_PRIVATE_KEY and RYT_JWT_PUBLIC_KEY.")]
+    MissingKeyPath(Algorithm),
+
+    #[error("The JWT algorithm {0:?} is not supported, use HS256, RS256 or ES256.")]
+    UnsupportedAlgorithm(Algorithm)
+}
+
+/// The algorithm and keys the application JWTs
+/// are signed and verified with.
+///
+/// For `HS256` a random secret is generated on startup,
+/// for `RS256` and `ES256` PEM keys are read from the
+/// configured paths, so other services can verify
+/// the tokens with the public key alone.
+#[derive(Clone)]
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey
+}
+
+impl JwtKeys {
+    /// Loads the keys for the algorithm in `RYT_JWT_ALGORITHM`.
+    pub fn from_config(config: &ReddytConfig) -> Result<Self, JwtKeysError> {
+        let algorithm = config.jwt_algorithm();
+
+        let (encoding, decoding) = match algorithm {
+            Algorithm::HS256 => {
+                let secret = get_jwt_secret()?.as_bytes();
+
+                (EncodingKey::from_secret(secret), DecodingKey::from_secret(secret))
+            }
+
+            Algorithm::RS256 => {
+                let (private_key, public_key) = read_key_pair(config, algorithm)?;
+
+                (EncodingKey::from_rsa_pem(&private_key)?, DecodingKey::from_rsa_pem(&public_key)?)
+            }
+
+            Algorithm::ES256 => {
+                let (private_key, public_key) = read_key_pair(config, algorithm)?;
+
+                (EncodingKey::from_ec_pem(&private_key)?, DecodingKey::from_ec_pem(&public_key)?)
+            }
+
+            algorithm => return Err(JwtKeysError::UnsupportedAlgorithm(algorithm))
+        };
+
+        Ok(Self {
+            algorithm,
+            encoding,
+            decoding
+        })
+    }
+
+    /// The algorithm tokens are signed with.
+    #[inline]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// The key tokens are signed with.
+    #[inline]
+    pub fn encoding(&self) -> &EncodingKey {
+        &self.encoding
+    }
+
+    /// The key tokens are verified with.
+    #[inline]
+    pub fn decoding(&self) -> &DecodingKey {
+        &self.decoding
+    }
+}
+
+impl Debug for JwtKeys {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        // The keys are secret, never print them.
+        f.debug_struct("JwtKeys")
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Reads the configured private and public PEM keys.
+fn read_key_pair(
+    config: &ReddytConfig,
+    algorithm: Algorithm
+) -> Result<(Vec<u8>, Vec<u8>), JwtKeysError> {
+    let (Some(private_key), Some(public_key)) = (config.jwt_private_key(), config.jwt_public_key())
+    else {
+        return Err(JwtKeysError::MissingKeyPath(algorithm));
+    };
+
+    Ok((read(private_key)?, read(public_key)?))
+}
+
+/// Stores a randomly generated secret to be used
+/// for JWT encrypting and decrypting.
+///
+/// This ensures the secret to be always the same.
+///
+/// XXX: The JWT may be rotated if needed.
+fn get_jwt_secret() -> Result<&'static String, OsRngError> {
+    static SECRET: OnceLock<String> = OnceLock::new();
+
+    let mut rng = StdRng::try_from_rng(&mut OsRng)?;
+
+    Ok(
+        SECRET
+            .get_or_init(|| {
+                Alphanumeric
+                    .sample_string(&mut rng, 32)
+            })
+    )
+}
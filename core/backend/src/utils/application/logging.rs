@@ -0,0 +1,78 @@
+use std::error::Error as StdError;
+use std::str::FromStr;
+
+use envconfig::{Envconfig, Error as EnvconfigError};
+use thiserror::Error;
+use tracing_subscriber::fmt;
+use tracing_subscriber::EnvFilter;
+
+/// The log filter used when `RUST_LOG` is not set.
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Holds any errors related to the logging setup.
+#[derive(Error, Debug)]
+pub enum LoggingError {
+    #[error("Couldn't load logging configuration from the environment, {0:#}")]
+    Envconfig(#[from] EnvconfigError),
+
+    #[error("The log format \"{0}\" at RYT_LOG_FORMAT is not one of pretty or json.")]
+    InvalidFormat(String),
+
+    #[error("Couldn't initialize the log subscriber, {0:#}")]
+    Subscriber(Box<dyn StdError + Send + Sync>)
+}
+
+/// How log records are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable lines, meant for development.
+    Pretty,
+
+    /// One JSON object per record, meant for
+    /// log aggregation in production.
+    Json
+}
+
+impl FromStr for LogFormat {
+    type Err = LoggingError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            _ => Err(LoggingError::InvalidFormat(value.to_string()))
+        }
+    }
+}
+
+/// The logging relevant environment variables.
+///
+/// This is loaded apart from `ReddytConfig` since
+/// logging must be set up before the configuration
+/// validation errors are logged.
+#[derive(Debug, Envconfig)]
+struct LoggingConfig {
+    #[envconfig(from = "RYT_LOG_FORMAT", default = "pretty")]
+    format: LogFormat
+}
+
+/// Initializes the global `tracing` subscriber with the
+/// format in `RYT_LOG_FORMAT` and the filter in `RUST_LOG`.
+///
+/// The subscriber installs the `tracing-log` bridge, so
+/// the existing `log` macro calls flow trough it too.
+pub fn init_logging() -> Result<(), LoggingError> {
+    let config = LoggingConfig::init_from_env()?;
+
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+
+    let builder = fmt()
+        .with_env_filter(filter);
+
+    match config.format {
+        LogFormat::Pretty => builder.try_init(),
+        LogFormat::Json => builder.json().try_init()
+    }
+        .map_err(LoggingError::Subscriber)
+}
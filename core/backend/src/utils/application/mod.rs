@@ -1,3 +1,9 @@
+pub mod cache;
 pub mod environment;
 pub mod context;
 pub mod errors;
+pub mod jwt;
+pub mod logging;
+pub mod notifications;
+pub mod secret;
+pub mod templates;
@@ -0,0 +1,190 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use actix_web::rt::spawn;
+use async_trait::async_trait;
+use futures::future::join_all;
+use reqwest::Client;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::runs::{Run, RunErrorKind};
+use crate::utils::application::environment::ReddytConfig;
+use crate::utils::external::email::{EmailError, EmailNotifier};
+use crate::utils::external::webhooks::{Webhook, WebhookError};
+
+/// The terminal state of a run.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Finished,
+    Errored
+}
+
+/// A run that reached a terminal state, this is
+/// what every notification channel receives.
+///
+/// The JSON structure is the following
+/// ```json
+/// {
+///     "profile_id": 1,
+///     "run_id": 1,
+///     "state": "finished" | "errored",
+///     "error": "..." | null,
+///     "error_kind": "TRANSIENT" | "CONFIGURATION" | "PROVIDER" | "INTERNAL" | null,
+///     "upload_url": "..." | null
+/// }
+/// ```
+#[derive(Serialize, Debug, Clone)]
+pub struct RunEvent {
+    profile_id: i32,
+    run_id: i32,
+    state: RunState,
+    error: Option<String>,
+    error_kind: Option<RunErrorKind>,
+    upload_url: Option<String>
+}
+
+impl RunEvent {
+    /// Creates an event for a run that reached a
+    /// terminal state, the state is derived from
+    /// whether the run has an error.
+    #[allow(dead_code)]
+    pub fn from_run(run: &Run, upload_url: Option<String>) -> Self {
+        let error = run.error().cloned();
+
+        Self {
+            profile_id: run.profile_id(),
+            run_id: run.id(),
+            state: match error {
+                Some(_) => RunState::Errored,
+                None => RunState::Finished
+            },
+            error,
+            error_kind: run.error_kind(),
+            upload_url
+        }
+    }
+
+    /// The profile the run belongs to.
+    #[inline]
+    pub fn profile_id(&self) -> i32 {
+        self.profile_id
+    }
+
+    /// The run that reached a terminal state.
+    #[inline]
+    pub fn run_id(&self) -> i32 {
+        self.run_id
+    }
+
+    /// The terminal state the run reached.
+    #[inline]
+    pub fn state(&self) -> RunState {
+        self.state
+    }
+
+    /// The run error if it errored.
+    #[inline]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// Holds any error a notification channel may
+/// fail with while delivering an event.
+#[derive(Error, Debug)]
+pub enum NotifyError {
+    #[error("{0:#}")]
+    Webhook(#[from] WebhookError),
+
+    #[error("{0:#}")]
+    Email(#[from] EmailError)
+}
+
+/// A destination run events are delivered to.
+///
+/// Channels decide on their own which events they
+/// care about, skipping an event is a success.
+#[async_trait]
+pub trait NotificationChannel: Debug + Send + Sync {
+    /// A human readable name for this channel,
+    /// used to report delivery failures.
+    fn name(&self) -> &'static str;
+
+    /// Delivers an event trough this channel.
+    async fn notify(&self, event: &RunEvent) -> Result<(), NotifyError>;
+}
+
+/// Fans run events out to every configured
+/// notification channel concurrently.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    channels: Vec<Arc<dyn NotificationChannel>>
+}
+
+impl Notifier {
+    /// Creates the notifier registering the channels
+    /// enabled in the configuration, channels that are
+    /// not configured are skipped.
+    ///
+    /// HTTP channels reuse the shared `http_client`.
+    pub fn from_config(config: &ReddytConfig, http_client: &Client) -> Result<Self, EmailError> {
+        let mut channels: Vec<Arc<dyn NotificationChannel>> = Vec::new();
+
+        if let Some(url) = config.webhook_url() {
+            channels.push(Arc::new(Webhook::new(
+                http_client.clone(),
+                url.expose().clone(),
+                config.webhook_events()
+            )));
+        }
+
+        if let Some(email) = EmailNotifier::from_config(config)? {
+            channels.push(Arc::new(email));
+        }
+
+        Ok(Self { channels })
+    }
+
+    /// Delivers an event to every channel concurrently,
+    /// a failing channel doesn't abort the others, the
+    /// failures are collected by channel name.
+    pub async fn dispatch(&self, event: &RunEvent) -> Vec<(&'static str, NotifyError)> {
+        join_all(
+            self.channels
+                .iter()
+                .map(|channel| async move {
+                    channel
+                        .notify(event)
+                        .await
+                        .err()
+                        .map(|error| (channel.name(), error))
+                })
+        )
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Dispatches the event in the background, delivery
+    /// failures are logged and never propagated, so they
+    /// can't fail the run nor block the scheduler.
+    ///
+    /// Nothing finishes runs before the pipeline is
+    /// spawned by the binary, so this has no caller.
+    #[allow(dead_code)]
+    pub fn notify(&self, event: RunEvent) {
+        let notifier = self.clone();
+
+        spawn(async move {
+            for (channel, error) in notifier.dispatch(&event).await {
+                log::error!(
+                    "Couldn't deliver {channel} notification for run {}, {error:#}",
+                    event.run_id()
+                );
+            }
+        });
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+/// What a secret prints as when formatted.
+const REDACTED: &str = "***";
+
+/// A value that must never end up in logs, formatting
+/// it with `Debug` or `Display` prints `***`, so it can
+/// live in structures that derive `Debug`.
+///
+/// The value is only obtained trough `expose`, which
+/// makes every place reading it explicit.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    /// The wrapped value, it must
+    /// not be logged nor formatted.
+    #[inline]
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> Display for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: FromStr> FromStr for Secret<T> {
+    type Err = T::Err;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value
+            .parse()
+            .map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_redacted_when_formatted() {
+        let secret = Secret("hunter2".to_string());
+
+        assert_eq!(format!("{secret:?}"), REDACTED);
+        assert_eq!(format!("{secret}"), REDACTED);
+        assert_eq!(format!("{:?}", Some(&secret)), "Some(***)");
+    }
+
+    #[test]
+    fn secrets_are_parsed_and_exposed() {
+        let secret = "42".parse::<Secret<u16>>()
+            .expect("The value parses");
+
+        assert_eq!(*secret.expose(), 42);
+    }
+}
@@ -0,0 +1,73 @@
+/// Renders a template replacing every `{{name}}` placeholder
+/// with its value in `variables`, i.e `{{question}}` is
+/// replaced by the value named `question`.
+///
+/// Whitespace inside the braces is ignored, placeholders
+/// without a value are left untouched so they are visible
+/// in the output rather than silently removed.
+///
+/// The profile title and description templates are
+/// rendered by the upload step, which doesn't exist yet.
+#[allow(dead_code)]
+pub fn render_template(template: &str, variables: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start + 2..].find("}}") else {
+            break;
+        };
+
+        let placeholder = &rest[start..start + 2 + length + 2];
+        let name = rest[start + 2..start + 2 + length].trim();
+
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(
+            variables
+                .iter()
+                .find(|(variable, _)| *variable == name)
+                .map_or(placeholder, |(_, value)| *value)
+        );
+
+        rest = &rest[start + placeholder.len()..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VARIABLES: [(&str, &str); 2] = [("question", "Why is the sky blue?"), ("profile", "science")];
+
+    #[test]
+    fn placeholders_are_substituted() {
+        assert_eq!(
+            render_template("{{question}} #{{profile}} #{{ profile }}", &VARIABLES),
+            "Why is the sky blue? #science #science"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        assert_eq!(
+            render_template("{{question}} by {{ author }}", &VARIABLES),
+            "Why is the sky blue? by {{ author }}"
+        );
+    }
+
+    #[test]
+    fn values_are_not_rendered_again() {
+        assert_eq!(
+            render_template("{{question}}", &[("question", "{{profile}}"), ("profile", "science")]),
+            "{{profile}}"
+        );
+    }
+
+    #[test]
+    fn unclosed_placeholders_are_kept() {
+        assert_eq!(render_template("{{question} {{question", &VARIABLES), "{{question} {{question");
+    }
+}
@@ -1,8 +1,23 @@
-use sqlx::migrate::{MigrateError, Migrator};
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use actix_web::rt::spawn;
+use actix_web::rt::time::interval;
+use sqlx::migrate::MigrateError;
 use sqlx::postgres::PgPoolOptions;
-use sqlx::{Error as SqlxError, Pool, Postgres};
+use sqlx::{query, Executor, Error as SqlxError, Pool, Postgres};
 use thiserror::Error;
 
+/// The threshold used if `set_slow_query_threshold`
+/// was never called.
+const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(200);
+
+/// Queries taking longer than this are logged,
+/// set once at startup from the configuration.
+static SLOW_QUERY_THRESHOLD: OnceLock<Duration> = OnceLock::new();
+
 #[derive(Error, Debug)]
 pub enum DbConnectionError {
     #[error("{0:#}")]
@@ -12,11 +27,189 @@ pub enum DbConnectionError {
     MigrateError(#[from] MigrateError),
 }
 
+/// Creates the connection pool, connections are tested
+/// before being handed out so the ones that died with
+/// a database restart are replaced instead of failing
+/// the next query.
 pub async fn init_db_connection(db_url: &str) -> Result<Pool<Postgres>, DbConnectionError> {
     let pool = PgPoolOptions::new()
         .max_connections(5)
+        .test_before_acquire(true)
+        .after_connect(|connection, _| Box::pin(async move {
+            // Identifies the application connections
+            // in `pg_stat_activity`.
+            connection
+                .execute("SET application_name = 'reddyt'")
+                .await?;
+
+            log::debug!("Opened a new database connection.");
+
+            Ok(())
+        }))
         .connect(db_url)
         .await?;
 
     Ok(pool)
 }
+
+/// Tracks whether the database is reachable, updated
+/// by a background check, the readiness endpoint
+/// reports it.
+#[derive(Debug, Clone)]
+pub struct DatabaseHealth {
+    healthy: Arc<AtomicBool>
+}
+
+impl DatabaseHealth {
+    /// Spawns the background check, which runs `SELECT 1`
+    /// every `check_interval`, keeping connections warm
+    /// and logging every transition between healthy and
+    /// unhealthy.
+    ///
+    /// The database is assumed healthy until the first
+    /// check, as the pool was just connected.
+    pub fn spawn(pool: Arc<Pool<Postgres>>, check_interval: Duration) -> Self {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let task_healthy = healthy.clone();
+
+        spawn(async move {
+            let mut ticker = interval(check_interval);
+
+            loop {
+                ticker.tick().await;
+
+                let result = query("SELECT 1")
+                    .execute(&*pool)
+                    .await;
+
+                let was_healthy = task_healthy.swap(result.is_ok(), Ordering::Relaxed);
+
+                match result {
+                    Err(error) if was_healthy => log::error!(
+                        "The database became unreachable, {error:#}"
+                    ),
+
+                    Ok(_) if !was_healthy => log::info!(
+                        "The database is reachable again."
+                    ),
+
+                    _ => {}
+                }
+            }
+        });
+
+        Self { healthy }
+    }
+
+    /// Whether the last check reached the database.
+    #[inline]
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+}
+
+/// Sets the duration from which `timed_query` logs a
+/// query as slow, only the first call has effect.
+pub fn set_slow_query_threshold(threshold: Duration) {
+    let _ = SLOW_QUERY_THRESHOLD.set(threshold);
+}
+
+/// The duration from which `timed_query` logs a query
+/// as slow, the default if it was never set.
+fn slow_query_threshold() -> Duration {
+    SLOW_QUERY_THRESHOLD
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_SLOW_QUERY_THRESHOLD)
+}
+
+/// Awaits a model query, logging it at `warn` with its
+/// label and elapsed time if it took longer than the
+/// slow query threshold.
+///
+/// The label identifies the query in logs, models use
+/// `table.method`, i.e `runs.count_active`.
+pub async fn timed_query<T>(label: &str, query: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let result = query.await;
+    let elapsed = start.elapsed();
+
+    let threshold = slow_query_threshold();
+
+    if elapsed > threshold {
+        log::warn!("Slow query {label} took {elapsed:?}, over the {threshold:?} threshold.");
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use actix_web::rt::time::sleep;
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+
+    use super::*;
+
+    /// Keeps every warning logged during the tests,
+    /// as the logger is global they are shared.
+    struct CapturingLogger {
+        warnings: Mutex<Vec<String>>
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Warn
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                self.warnings
+                    .lock()
+                    .unwrap()
+                    .push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger {
+        warnings: Mutex::new(Vec::new())
+    };
+
+    /// Installs `LOGGER`, only the first call has effect.
+    fn capture_warnings() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(LevelFilter::Warn);
+    }
+
+    /// Whether a slow query warning for
+    /// the label was logged.
+    fn warned_about(label: &str) -> bool {
+        LOGGER.warnings
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|warning| warning.contains(&format!("Slow query {label} ")))
+    }
+
+    #[actix_web::test]
+    async fn slow_queries_are_logged() {
+        capture_warnings();
+
+        timed_query("tests.slow", sleep(slow_query_threshold() + Duration::from_millis(50))).await;
+
+        assert!(warned_about("tests.slow"));
+    }
+
+    #[actix_web::test]
+    async fn fast_queries_are_not_logged() {
+        capture_warnings();
+
+        timed_query("tests.fast", async {}).await;
+
+        assert!(!warned_about("tests.fast"));
+    }
+}
@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use lettre::address::AddressError;
+use lettre::error::Error as MessageError;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::Error as SmtpError;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use thiserror::Error;
+
+use crate::utils::application::environment::ReddytConfig;
+use crate::utils::application::notifications::{NotificationChannel, NotifyError, RunEvent};
+
+/// Holds any errors related to email delivery.
+#[derive(Error, Debug)]
+pub enum EmailError {
+    #[error("Error while connecting to the SMTP server, {0:#}")]
+    Smtp(#[from] SmtpError),
+
+    #[error("Invalid email address, {0:#}")]
+    Address(#[from] AddressError),
+
+    #[error("Error while building the email, {0:#}")]
+    Message(#[from] MessageError)
+}
+
+/// An SMTP notifier that emails the admin
+/// when a run errors.
+#[derive(Debug, Clone)]
+pub struct EmailNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Mailbox,
+    public_url: Option<String>
+}
+
+impl EmailNotifier {
+    /// Creates the notifier from the `RYT_SMTP_*` variables,
+    /// if `RYT_SMTP_HOST` is not set this returns Ok(None)
+    /// and email notifications are a no-op.
+    pub fn from_config(config: &ReddytConfig) -> Result<Option<Self>, EmailError> {
+        let Some(host) = config.smtp_host() else {
+            return Ok(None);
+        };
+
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+            .port(config.smtp_port());
+
+        if let (Some(username), Some(password)) = (config.smtp_username(), config.smtp_password()) {
+            transport = transport
+                .credentials(Credentials::new(username.to_string(), password.expose().clone()));
+        }
+
+        // The sender falls back to the admin email, which
+        // is always the recipient.
+        let from = config
+            .smtp_from()
+            .unwrap_or(config.admin_email())
+            .parse()?;
+
+        Ok(Some(Self {
+            transport: transport.build(),
+            from,
+            to: config.admin_email().parse()?,
+            public_url: config.public_url().map(ToString::to_string)
+        }))
+    }
+
+    /// Emails the admin about a failed run, events
+    /// for runs that didn't error are skipped.
+    pub async fn send_failure(&self, event: &RunEvent) -> Result<(), EmailError> {
+        let Some(error) = event.error() else {
+            return Ok(());
+        };
+
+        let mut body = format!(
+            "Run {} for profile {} failed with the following error.\n\n{error}\n",
+            event.run_id(),
+            event.profile_id()
+        );
+
+        if let Some(public_url) = &self.public_url {
+            body.push_str(&format!(
+                "\nSee the run at {}/runs/{}\n",
+                public_url.trim_end_matches('/'),
+                event.run_id()
+            ));
+        }
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(self.to.clone())
+            .subject(format!("[reddyt] Run {} failed", event.run_id()))
+            .body(body)?;
+
+        self.transport
+            .send(message)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for EmailNotifier {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    async fn notify(&self, event: &RunEvent) -> Result<(), NotifyError> {
+        Ok(self.send_failure(event).await?)
+    }
+}
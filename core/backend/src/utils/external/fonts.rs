@@ -0,0 +1,185 @@
+use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
+
+use reqwest::{Client, Error as ReqwestError, StatusCode};
+use thiserror::Error;
+use tokio::fs::{create_dir_all, remove_file, rename, try_exists, write};
+
+use crate::models::runs::{RunErrorKind, RunErrorSource};
+
+/// The Google Fonts CSS API, this returns a stylesheet
+/// containing the font file URLs for the requested family.
+///
+/// see: https://developers.google.com/fonts/docs/css2
+const GOOGLE_FONTS_CSS_URL: &str = "https://fonts.googleapis.com/css2";
+
+/// The font file extensions that may be served by Google Fonts
+/// to a non browser user agent.
+const FONT_EXTENSIONS: [&str; 2] = ["ttf", "otf"];
+
+/// Holds any errors related to fetching or caching fonts.
+#[derive(Error, Debug)]
+pub enum FontError {
+    #[error("Error while requesting Google Fonts, {0:#}")]
+    Request(#[from] ReqwestError),
+
+    #[error("Error while accessing the font cache, {0:#}")]
+    Io(#[from] IoError),
+
+    #[error("The font family \"{0}\" with weight {1} doesn't exist in Google Fonts.")]
+    UnknownFamily(String, u16),
+
+    #[error("Google Fonts didn't return a usable font file for \"{0}\".")]
+    MissingSource(String)
+}
+
+impl RunErrorSource for FontError {
+    fn kind(&self) -> RunErrorKind {
+        match self {
+            Self::Request(error) if error.is_timeout() || error.is_connect() => RunErrorKind::Transient,
+            Self::Request(_) | Self::MissingSource(_) => RunErrorKind::Provider,
+            Self::Io(_) => RunErrorKind::Internal,
+            Self::UnknownFamily(..) => RunErrorKind::Configuration
+        }
+    }
+}
+
+/// Obtains the path to a font file for a Google Fonts family
+/// and weight, downloading it into `cache_dir` only if it
+/// wasn't already cached.
+///
+/// The family is the human readable name, as in `Open Sans`,
+/// spaces are handled both for the request and the cached file.
+///
+/// The client should be the shared application one.
+///
+/// There is no compositor resolving stage fonts yet,
+/// so this has no caller.
+#[allow(dead_code)]
+pub async fn fetch_font(
+    client: &Client,
+    cache_dir: &Path,
+    family: &str,
+    weight: u16
+) -> Result<PathBuf, FontError> {
+    let cache_key = font_cache_key(family, weight);
+
+    // If any of the possible files was already
+    // downloaded, skip the request entirely.
+    for extension in FONT_EXTENSIONS {
+        let cached = cache_dir.join(format!("{cache_key}.{extension}"));
+
+        if try_exists(&cached).await? {
+            return Ok(cached);
+        }
+    }
+
+    // Google Fonts serves a stylesheet with an `src: url(...)`
+    // per font face, a missing family is reported as 400.
+    let stylesheet = client
+        .get(GOOGLE_FONTS_CSS_URL)
+        .query(&[("family", format!("{family}:wght@{weight}"))])
+        .send()
+        .await?;
+
+    if stylesheet.status() == StatusCode::BAD_REQUEST {
+        return Err(FontError::UnknownFamily(family.to_string(), weight));
+    }
+
+    let stylesheet = stylesheet
+        .error_for_status()?
+        .text()
+        .await?;
+
+    let (source, extension) = find_font_source(&stylesheet)
+        .ok_or_else(|| FontError::MissingSource(family.to_string()))?;
+
+    let font_bytes = client
+        .get(source)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    create_dir_all(cache_dir).await?;
+
+    // The font is written to a temporary file and renamed,
+    // which is atomic within a directory, so a partially
+    // written font is never taken as a cache hit.
+    let path = cache_dir.join(format!("{cache_key}.{extension}"));
+    let temporary = cache_dir.join(format!("{cache_key}.{extension}.{:016x}.tmp", rand::random::<u64>()));
+
+    if let Err(error) = write(&temporary, &font_bytes).await {
+        let _ = remove_file(&temporary).await;
+        return Err(error.into());
+    }
+
+    rename(&temporary, &path).await?;
+
+    Ok(path)
+}
+
+/// The file name a font is cached as, without extension.
+///
+/// The family is lowercased and anything that is not an
+/// ASCII letter or digit is replaced, so `Open Sans` with
+/// weight 700 becomes `open_sans-700`.
+///
+/// The key only contains `[a-z0-9_-]`, so a family such
+/// as `../x` can't escape the cache directory.
+fn font_cache_key(family: &str, weight: u16) -> String {
+    let family: String = family
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|character| match character {
+            'a'..='z' | '0'..='9' => character,
+            _ => '_'
+        })
+        .collect();
+
+    format!("{family}-{weight}")
+}
+
+/// Finds the first `url(...)` in a Google Fonts stylesheet
+/// pointing to a known font file, returning the URL and
+/// its extension.
+fn find_font_source(stylesheet: &str) -> Option<(&str, &'static str)> {
+    stylesheet
+        .split("url(")
+        .skip(1)
+        .filter_map(|part| part.split_once(')'))
+        .map(|(url, _)| url.trim_matches(['\'', '"']))
+        .find_map(|url| {
+            FONT_EXTENSIONS
+                .into_iter()
+                .find(|extension| url.ends_with(&format!(".{extension}")))
+                .map(|extension| (url, extension))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_key_replaces_spaces() {
+        assert_eq!(font_cache_key(" Open Sans ", 700), "open_sans-700");
+    }
+
+    #[test]
+    fn cache_key_cant_traverse_directories() {
+        let key = font_cache_key("../../etc/passwd", 400);
+
+        assert_eq!(key, "______etc_passwd-400");
+        assert!(key.chars().all(|character| matches!(character, 'a'..='z' | '0'..='9' | '_' | '-')));
+    }
+
+    #[test]
+    fn finds_the_first_known_font_source() {
+        let stylesheet = "src: url(https://x/a.woff2) format('woff2');\nsrc: url('https://x/b.ttf') format('truetype');";
+
+        assert_eq!(find_font_source(stylesheet), Some(("https://x/b.ttf", "ttf")));
+    }
+}
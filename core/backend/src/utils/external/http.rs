@@ -0,0 +1,44 @@
+use reqwest::{Client, Error as ReqwestError, NoProxy, Proxy};
+
+use crate::utils::application::environment::ReddytConfig;
+
+/// The user agent sent with every outbound request.
+const USER_AGENT: &str = concat!("reddyt/", env!("CARGO_PKG_VERSION"));
+
+/// Builds the HTTP client shared by every outbound
+/// integration, so connections are pooled and no
+/// request can hang past the configured timeouts.
+///
+/// A request exceeding the timeout fails with a
+/// reqwest error where `is_timeout()` is true.
+///
+/// If `RYT_HTTP_PROXY` or `RYT_HTTPS_PROXY` are set every
+/// outbound call, text generation, TTS, uploads, webhooks
+/// and fonts, goes trough them except for the hosts in
+/// `RYT_NO_PROXY`.
+pub fn build_http_client(config: &ReddytConfig) -> Result<Client, ReqwestError> {
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(config.http_connect_timeout())
+        .timeout(config.http_timeout());
+
+    let no_proxy = config
+        .no_proxy()
+        .and_then(NoProxy::from_string);
+
+    if let Some(http_proxy) = config.http_proxy() {
+        builder = builder.proxy(
+            Proxy::http(http_proxy.expose())?
+                .no_proxy(no_proxy.clone())
+        );
+    }
+
+    if let Some(https_proxy) = config.https_proxy() {
+        builder = builder.proxy(
+            Proxy::https(https_proxy.expose())?
+                .no_proxy(no_proxy)
+        );
+    }
+
+    builder.build()
+}
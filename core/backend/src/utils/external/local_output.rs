@@ -0,0 +1,155 @@
+use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use reqwest::Url;
+use thiserror::Error;
+use tokio::fs::{canonicalize, create_dir_all, remove_file, File, OpenOptions};
+use tokio::io::copy;
+
+use crate::models::runs::{RunErrorKind, RunErrorSource};
+
+/// Holds any errors related to persisting
+/// videos for the `Local` upload platform.
+#[derive(Error, Debug)]
+pub enum LocalOutputError {
+    #[error("Error while writing to the output directory, {0:#}")]
+    Io(#[from] IoError),
+
+    #[error("The output path \"{0}\" can't be represented as a file URL.")]
+    InvalidPath(PathBuf)
+}
+
+impl RunErrorSource for LocalOutputError {
+    fn kind(&self) -> RunErrorKind {
+        match self {
+            Self::Io(_) => RunErrorKind::Internal,
+            Self::InvalidPath(_) => RunErrorKind::Configuration
+        }
+    }
+}
+
+/// Persists a composed video for the `Local` upload
+/// platform, copying it into `output_dir` which is
+/// created if missing.
+///
+/// The file is named after the profile, the current
+/// UTC time and the run, as in `my_profile-20250101T120000Z-42.mp4`,
+/// the returned `file://` URL is what gets stored as
+/// the upload generated URL.
+///
+/// The run id keeps names of runs finishing in the same
+/// second apart, if a file with the name exists anyway
+/// the copy fails rather than overwriting it.
+///
+/// Only the upload step of the run pipeline persists
+/// videos, and that pipeline isn't spawned yet.
+#[allow(dead_code)]
+pub async fn persist_video(
+    output_dir: &Path,
+    profile_name: &str,
+    run_id: i32,
+    video: &Path
+) -> Result<Url, LocalOutputError> {
+    create_dir_all(output_dir).await?;
+
+    let extension = video
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| format!(".{}", sanitize_file_name(extension)))
+        .unwrap_or_default();
+
+    let file_name = format!(
+        "{}-{}-{run_id}{extension}",
+        sanitize_file_name(profile_name),
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    );
+
+    // The URL requires an absolute path, the output
+    // directory may be configured as a relative one.
+    let path = canonicalize(output_dir)
+        .await?
+        .join(file_name);
+
+    let mut source = File::open(video).await?;
+    let mut target = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .await?;
+
+    // A partial copy would be taken for the video,
+    // the file is only kept once fully written.
+    if let Err(error) = copy(&mut source, &mut target).await {
+        let _ = remove_file(&path).await;
+        return Err(error.into());
+    }
+
+    Url::from_file_path(&path)
+        .map_err(|_| LocalOutputError::InvalidPath(path))
+}
+
+/// Reduces a name to characters that are safe in file
+/// names on every platform, anything else becomes an
+/// underscore, so `My Profile/2` becomes `my_profile_2`.
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized = name
+        .trim()
+        .to_lowercase()
+        .replace(|character: char| !character.is_ascii_alphanumeric() && character != '-', "_");
+
+    if sanitized.is_empty() {
+        return "video".to_string();
+    }
+
+    sanitized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use tempfile::tempdir;
+    use tokio::fs::write;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn runs_of_the_same_profile_get_their_own_file() {
+        let directory = tempdir().unwrap();
+        let video = directory.path().join("video.mp4");
+        write(&video, b"video").await.unwrap();
+
+        let output_dir = directory.path().join("output");
+        let first = persist_video(&output_dir, "My Profile", 1, &video).await.unwrap();
+        let second = persist_video(&output_dir, "My Profile", 2, &video).await.unwrap();
+
+        assert_ne!(first, second);
+        assert!(first.path().ends_with("-1.mp4"));
+        assert!(second.path().ends_with("-2.mp4"));
+    }
+
+    #[actix_web::test]
+    async fn existing_files_are_not_overwritten() {
+        let directory = tempdir().unwrap();
+        let video = directory.path().join("video.mp4");
+        write(&video, b"video").await.unwrap();
+
+        let output_dir = directory.path().join("output");
+
+        // The same run persisted twice within a second
+        // collides, it's retried if a second boundary
+        // is crossed in between.
+        let result = loop {
+            match persist_video(&output_dir, "profile", 1, &video).await {
+                Ok(_) => continue,
+                result => break result
+            }
+        };
+
+        assert!(matches!(
+            result,
+            Err(LocalOutputError::Io(error)) if error.kind() == ErrorKind::AlreadyExists
+        ));
+    }
+}
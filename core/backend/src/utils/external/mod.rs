@@ -1,2 +1,8 @@
 
 pub mod database;
+pub mod email;
+pub mod fonts;
+pub mod http;
+pub mod local_output;
+pub mod webhooks;
+pub mod work_dir;
@@ -0,0 +1,191 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use actix_web::rt::time::sleep;
+use async_trait::async_trait;
+use reqwest::{Client, Error as ReqwestError, StatusCode};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::utils::application::notifications::{NotificationChannel, NotifyError, RunEvent, RunState};
+
+/// How many times a webhook delivery is attempted
+/// before giving up.
+const WEBHOOK_ATTEMPTS: u32 = 4;
+/// The delay before the first retry, doubled
+/// on every subsequent attempt.
+const WEBHOOK_BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Holds any errors related to webhook delivery.
+///
+/// Delivery errors never carry the request URL, Discord
+/// and Slack webhook URLs embed the token in the path.
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("Error while delivering the webhook, {0:#}")]
+    Delivery(ReqwestError),
+
+    #[error("The configured webhook events \"{0}\" are not one of all, success or failure.")]
+    InvalidEvents(String)
+}
+
+/// Which terminal run states trigger a webhook,
+/// configured trough `RYT_WEBHOOK_EVENTS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvents {
+    /// Both finished and errored runs.
+    All,
+
+    /// Only runs that finished without error.
+    Success,
+
+    /// Only runs that errored.
+    Failure
+}
+
+impl FromStr for WebhookEvents {
+    type Err = WebhookError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "all" => Ok(Self::All),
+            "success" => Ok(Self::Success),
+            "failure" => Ok(Self::Failure),
+            _ => Err(WebhookError::InvalidEvents(value.to_string()))
+        }
+    }
+}
+
+/// A configured webhook endpoint, notified when
+/// a run reaches a terminal state.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    client: Client,
+    url: String,
+    events: WebhookEvents
+}
+
+impl Webhook {
+    /// Creates a webhook for the given URL, the URL is
+    /// expected to be validated with the configuration.
+    ///
+    /// The client should be the shared application one.
+    pub fn new(client: Client, url: String, events: WebhookEvents) -> Self {
+        Self {
+            client,
+            url,
+            events
+        }
+    }
+
+    /// Whether the webhook is subscribed to a run state.
+    pub fn is_subscribed(&self, state: RunState) -> bool {
+        match self.events {
+            WebhookEvents::All => true,
+            WebhookEvents::Success => state == RunState::Finished,
+            WebhookEvents::Failure => state == RunState::Errored
+        }
+    }
+
+    /// POSTs the event as JSON, retrying with exponential
+    /// backoff up to `WEBHOOK_ATTEMPTS` times, responses
+    /// that can't succeed on retry fail right away, see
+    /// `is_permanent`.
+    ///
+    /// Unsubscribed states are skipped as a success,
+    /// see [`RunEvent`] for the payload shape.
+    pub async fn deliver(&self, event: &RunEvent) -> Result<(), WebhookError> {
+        if !self.is_subscribed(event.state()) {
+            return Ok(());
+        }
+
+        let mut backoff = WEBHOOK_BASE_BACKOFF;
+        let mut attempt = 1;
+
+        loop {
+            match self.send(event).await {
+                Ok(_) => return Ok(()),
+
+                Err(error) if attempt >= WEBHOOK_ATTEMPTS || error.status().is_some_and(is_permanent) => {
+                    return Err(WebhookError::Delivery(error));
+                }
+
+                Err(error) => {
+                    log::warn!(
+                        "Webhook delivery attempt {attempt} failed, retrying in {backoff:?}, {error:#}"
+                    );
+
+                    sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// A single delivery attempt, the error is
+    /// stripped of the URL before it's returned.
+    async fn send(&self, payload: &impl Serialize) -> Result<(), ReqwestError> {
+        self.client
+            .post(&self.url)
+            .json(payload)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(ReqwestError::without_url)
+    }
+}
+
+/// Whether a webhook response status fails the same
+/// way on retry, client errors do except for timeouts
+/// and rate limiting.
+fn is_permanent(status: StatusCode) -> bool {
+    status.is_client_error()
+        && status != StatusCode::REQUEST_TIMEOUT
+        && status != StatusCode::TOO_MANY_REQUESTS
+}
+
+#[async_trait]
+impl NotificationChannel for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn notify(&self, event: &RunEvent) -> Result<(), NotifyError> {
+        Ok(self.deliver(event).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_client_errors_are_permanent() {
+        assert!(is_permanent(StatusCode::BAD_REQUEST));
+        assert!(is_permanent(StatusCode::NOT_FOUND));
+        assert!(!is_permanent(StatusCode::REQUEST_TIMEOUT));
+        assert!(!is_permanent(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_permanent(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_permanent(StatusCode::BAD_GATEWAY));
+    }
+
+    #[actix_web::test]
+    async fn delivery_errors_dont_leak_the_url() {
+        let webhook = Webhook::new(
+            Client::new(),
+            "http://127.0.0.1:1/api/webhooks/1/secret-token".to_string(),
+            WebhookEvents::All
+        );
+
+        let error = webhook
+            .send(&())
+            .await
+            .map_err(WebhookError::Delivery)
+            .expect_err("Nothing listens on the port");
+
+        assert!(!format!("{error:#}").contains("secret-token"));
+        assert!(!format!("{error:?}").contains("secret-token"));
+    }
+}
@@ -0,0 +1,62 @@
+use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
+
+use tempfile::{Builder as TempDirBuilder, TempDir};
+use thiserror::Error;
+
+use crate::models::runs::{RunErrorKind, RunErrorSource};
+
+/// Holds any errors related to run work directories.
+#[derive(Error, Debug)]
+pub enum WorkDirError {
+    #[error("Error while creating the run work directory, {0:#}")]
+    Io(#[from] IoError)
+}
+
+impl RunErrorSource for WorkDirError {
+    fn kind(&self) -> RunErrorKind {
+        RunErrorKind::Internal
+    }
+}
+
+/// The directory a single run writes every intermediate
+/// artifact to, audio, frames and video, so cleaning up
+/// is a single directory removal.
+///
+/// The directory is removed when this is dropped, also
+/// when the run panics, unless it's kept with `retain`.
+///
+/// Runs are only executed by the pipeline, which the
+/// binary doesn't spawn yet, so none is created.
+#[derive(Debug)]
+pub struct RunWorkDir {
+    directory: TempDir
+}
+
+#[allow(dead_code)]
+impl RunWorkDir {
+    /// Creates the directory for a run under `work_dir`,
+    /// which is created if missing, the run id is part of
+    /// the name so it can be found while debugging.
+    pub fn create(work_dir: &Path, run_id: i32) -> Result<Self, WorkDirError> {
+        std::fs::create_dir_all(work_dir)?;
+
+        let directory = TempDirBuilder::new()
+            .prefix(&format!("run-{run_id}-"))
+            .tempdir_in(work_dir)?;
+
+        Ok(Self { directory })
+    }
+
+    /// The path artifacts must be written under.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        self.directory.path()
+    }
+
+    /// Keeps the directory and its artifacts after
+    /// the run, returning its path.
+    pub fn retain(self) -> PathBuf {
+        self.directory.keep()
+    }
+}
@@ -1,42 +1,80 @@
-use std::future::{ready, Ready};
-use std::sync::OnceLock;
+use std::str::FromStr;
 
-use actix_failwrap::ErrorResponse;
+use actix_web::cookie::SameSite;
 use actix_web::http::header::AUTHORIZATION;
 use actix_web::web::Data;
 use actix_web::{FromRequest, HttpRequest};
 use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
 use chrono::{Utc, Duration};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use jsonwebtoken::errors::Error as JwtError;
-use rand::rand_core::OsError as OsRngError;
-use rand::rngs::{OsRng, StdRng};
-use rand::distr::{Alphanumeric, SampleString};
-use rand::SeedableRng;
+use futures::future::LocalBoxFuture;
+use jsonwebtoken::{decode, encode, Header, Validation};
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind as JwtErrorKind};
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use thiserror::Error;
 
+use crate::models::accounts::{
+    Account,
+    AccountCredentials,
+    AccountError,
+    AuthenticationResult,
+    LockoutPolicy
+};
 use crate::utils::application::context::AppContext;
-use crate::utils::application::errors::json_formatter;
+use crate::utils::application::errors::{http_error, ErrorCode, HttpError};
+use crate::utils::application::jwt::JwtKeys;
 
 /// The authentication cookie key
 pub const COOKIE_KEY: &str = "authentication";
 /// How long until the authentication session expires.
 pub const AUTH_EXPIRATION_HOURS: i64 = 3;
 
+/// The `SameSite` attribute for the authentication
+/// cookie, configured trough `RYT_COOKIE_SAMESITE`.
+///
+/// `None` requires the cookie to be `Secure`, which
+/// is enforced when the cookie is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CookieSameSite(SameSite);
+
+impl CookieSameSite {
+    /// The attribute to set on the cookie.
+    #[inline]
+    pub fn same_site(&self) -> SameSite {
+        self.0
+    }
+
+    /// Whether browsers will only accept the
+    /// cookie if it's marked as `Secure`.
+    #[inline]
+    pub fn requires_secure(&self) -> bool {
+        self.0 == SameSite::None
+    }
+}
+
+impl FromStr for CookieSameSite {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "strict" => Ok(Self(SameSite::Strict)),
+            "lax" => Ok(Self(SameSite::Lax)),
+            "none" => Ok(Self(SameSite::None)),
+            _ => Err(format!("\"{value}\" is not one of Strict, Lax or None."))
+        }
+    }
+}
+
 /// Holds any error that may occur during the authentication
 /// process with `OptionalAuth`.
-#[derive(ErrorResponse, Error, Debug)]
-#[transform_response(json_formatter)]
+#[derive(Error, Debug)]
 pub enum OptionalAuthError {
     #[error("Couldn't load application context.")]
     MissingContext,
 
-    #[error("Couldn't generate a valid JWT secret, {0:#}")]
-    JwtSecret(#[from] OsRngError),
-
     #[error("Couldn't obtain an expiration date for the JWT.")]
     JwtExpiration,
 
@@ -44,19 +82,90 @@ pub enum OptionalAuthError {
     JwtEncoding(#[from] JwtError),
 
     #[error("Attempted to perform a failing cast between two numeric values.")]
-    InvalidCast
+    InvalidCast,
+
+    #[error("Couldn't authenticate the account, {0:#}")]
+    Account(#[from] AccountError)
+}
+
+impl HttpError for OptionalAuthError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::MissingContext
+                | Self::JwtExpiration
+                | Self::JwtEncoding(_)
+                | Self::InvalidCast
+                | Self::Account(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::MissingContext
+                | Self::Account(AccountError::PasswordHash(_)) => ErrorCode::Internal,
+            Self::JwtExpiration
+                | Self::JwtEncoding(_)
+                | Self::InvalidCast => ErrorCode::TokenEncoding,
+            Self::Account(AccountError::DatabaseConnection(_)) => ErrorCode::Database
+        }
+    }
 }
 
+http_error!(OptionalAuthError);
+
 /// The claims that the application JWT consists of.
 ///
 /// The email is a filler and the expiration is
 /// managed by the jwt crate.
-#[derive(Serialize, Deserialize, Debug)]
-struct OptionalAuthClaims {
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OptionalAuthClaims {
     email: String,
     exp: usize
 }
 
+impl OptionalAuthClaims {
+    /// The email of the authenticated account.
+    #[inline]
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    /// When the token expires, as a UNIX
+    /// timestamp in seconds.
+    #[inline]
+    pub fn exp(&self) -> usize {
+        self.exp
+    }
+}
+
+/// Why a bearer token was not accepted, reported
+/// by token introspection.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenRejection {
+    #[error("expired")]
+    Expired,
+
+    #[error("invalid_signature")]
+    InvalidSignature,
+
+    #[error("malformed")]
+    Malformed,
+
+    #[error("unknown_email")]
+    UnknownEmail
+}
+
+impl From<JwtError> for TokenRejection {
+    fn from(error: JwtError) -> Self {
+        match error.kind() {
+            JwtErrorKind::ExpiredSignature => Self::Expired,
+            JwtErrorKind::InvalidSignature
+                | JwtErrorKind::InvalidAlgorithm => Self::InvalidSignature,
+            _ => Self::Malformed
+        }
+    }
+}
+
 /// `OptionalAuth` is an Actix Web extractor
 /// that handles optional basic authentication.
 ///
@@ -76,7 +185,8 @@ struct OptionalAuthClaims {
 /// see: https://www.rfc-editor.org/rfc/rfc9110.html
 /// about ignoring user errors while authenticating.
 pub struct OptionalAuth {
-    token: Option<String>
+    token: Option<String>,
+    claims: Option<OptionalAuthClaims>
 }
 
 impl OptionalAuth {
@@ -86,7 +196,8 @@ impl OptionalAuth {
     #[inline]
     const fn unauthenticated() -> Self {
         Self {
-            token: None
+            token: None,
+            claims: None
         }
     }
 
@@ -94,9 +205,10 @@ impl OptionalAuth {
     ///
     /// Acts as a shortener to avoid ambiguity.
     #[inline]
-    const fn authenticated(token: String) -> Self {
+    const fn authenticated(token: String, claims: OptionalAuthClaims) -> Self {
         Self {
-            token: Some(token)
+            token: Some(token),
+            claims: Some(claims)
         }
     }
 
@@ -109,6 +221,15 @@ impl OptionalAuth {
         self.token.as_ref()
     }
 
+    /// If the user is authenticated this returns the
+    /// claims of the issued JWT, otherwise None.
+    ///
+    /// The claims are decoded while authenticating,
+    /// so reading them doesn't query anything.
+    pub fn claims(&self) -> Option<&OptionalAuthClaims> {
+        self.claims.as_ref()
+    }
+
     /// Returns wether the user is authenticated
     /// or not.
     pub fn is_authenticated(&self) -> bool {
@@ -119,81 +240,75 @@ impl OptionalAuth {
 impl FromRequest for OptionalAuth {
     type Error = OptionalAuthError;
 
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        ready(try_authenticate(req))
+        // Obtain the application wide context.
+        let app_context = req
+            .app_data::<Data<AppContext>>()
+            .cloned();
+
+        // Attempt to obtain user-provided authentication
+        // string from the Authentication header.
+        //
+        // see: https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Headers/Authorization
+        let header_credentials = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .map(ToString::to_string);
+
+        // Attempt to obtain bearer token from a browser
+        // provided cookie.
+        let cookie_credentials = req
+            .cookie(COOKIE_KEY)
+            .map(|cookie| cookie
+                .value()
+                .to_owned()
+            );
+
+        Box::pin(try_authenticate(app_context, header_credentials, cookie_credentials))
     }
 }
 
-/// Stores a randomly generated secret to be used
-/// for JWT encrypting and decrypting.
-///
-/// This ensures the secret to be always the same.
-///
-/// XXX: The JWT may be rotated if needed.
-fn get_jwt_secret() -> Result<&'static String, OsRngError> {
-    static SECRET: OnceLock<String> = OnceLock::new();
-
-    let mut rng = StdRng::try_from_rng(&mut OsRng)?;
-
-    Ok(
-        SECRET
-            .get_or_init(|| {
-                Alphanumeric
-                    .sample_string(&mut rng, 32)
-            })
-    )
-}
-
 /// Error wrapper for the `FromRequest` middleware implementation for
-/// `OptionalAuth`. Used to avoid needing `ready` right away and being
-/// able to propagate errors within.
+/// `OptionalAuth`. Used to be able to propagate errors within.
+///
+/// The request parts are extracted beforehand, as the
+/// future can't borrow the request.
 ///
 /// This is strictly called in the earlier mentioned implementation.
-fn try_authenticate(req: &HttpRequest) -> Result<OptionalAuth, OptionalAuthError> {
-    // Obtain the application wide context.
-    //
-    // If this fails 500 error is thrown.
-    let app_context = req
-        .app_data::<Data<AppContext>>()
+async fn try_authenticate(
+    app_context: Option<Data<AppContext>>,
+    header_credentials: Option<String>,
+    cookie_credentials: Option<String>
+) -> Result<OptionalAuth, OptionalAuthError> {
+    // If the context is missing a 500 error is thrown.
+    let app_context = app_context
         .ok_or(OptionalAuthError::MissingContext)?;
 
-    let admin_email = app_context.config().admin_email();
-    let admin_password = app_context.config().admin_password();
-
-    // Attempt to obtain user-provided authentication
-    // string from the Authentication header.
-    //
-    // see: https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Headers/Authorization
-    let header_credentials = req
-        .headers()
-        .get(AUTHORIZATION)
-        .and_then(|header| header.to_str().ok())
-        .map(ToString::to_string);
+    let connection = app_context.get_db_connection();
+    let jwt_keys = app_context.jwt_keys();
 
     // If the header contains basic authentication, try to authenticate with it.
     if let Some(credentials) = header_credentials.as_ref().and_then(|c| c.strip_prefix("Basic ")) {
-        return try_authenticate_basic(credentials, admin_email, admin_password);
+        return try_authenticate_basic(
+            credentials,
+            &connection,
+            &LockoutPolicy::from_config(app_context.config()),
+            jwt_keys
+        )
+            .await;
     }
 
     // If the header contains bearer authentication, try to authenticate with it.
     if let Some(credentials) = header_credentials.as_ref().and_then(|c| c.strip_prefix("Bearer ")) {
-        return try_authenticate_bearer(credentials, admin_email);
+        return try_authenticate_bearer(credentials, &connection, jwt_keys).await;
     }
 
-    // Attempt to obtain bearer token from a browser
-    // provided cookie.
-    let cookie_credentials = req
-        .cookie(COOKIE_KEY)
-        .map(|cookie| cookie
-            .value()
-            .to_owned()
-        );
-
     // If there is a cookie, try to authenticate with it.
     if let Some(credentials) = cookie_credentials {
-        return try_authenticate_bearer(&credentials, admin_email);
+        return try_authenticate_bearer(&credentials, &connection, jwt_keys).await;
     }
 
     // Otherwise assume there is no authentication
@@ -201,43 +316,88 @@ fn try_authenticate(req: &HttpRequest) -> Result<OptionalAuth, OptionalAuthError
     Ok(OptionalAuth::unauthenticated())
 }
 
-/// Takes a "basic" authentication token.
+/// Decodes "basic" authentication credentials into
+/// the email and password they consist of.
+///
+/// The decoded credentials are split on the first colon,
+/// the admin email is validated to contain none, so any
+/// further colon belongs to the password, as RFC 7617
+/// allows, i.e `admin@host:pa:ss` has the password `pa:ss`.
+///
+/// Surrounding whitespace in the encoded token is ignored,
+/// while invalid base64, non UTF-8 credentials or missing
+/// colons return None.
 ///
 /// See: https://datatracker.ietf.org/doc/html/rfc7617
-fn try_authenticate_basic(
-    user_credentials: &str,
-    admin_email: &str,
-    admin_password: &str
-) -> Result<OptionalAuth, OptionalAuthError> {
-    // Decode the base64 string into bytes or return
-    // an unauthenticated response if the user provided
-    // an invalid base64 string.
-    let Ok(decoded_bytes) = BASE64_STANDARD.decode(user_credentials)
-    else {
-        return Ok(OptionalAuth::unauthenticated());
-    };
+fn parse_basic_credentials(user_credentials: &str) -> Option<(String, String)> {
+    // Decode the base64 string into bytes, the user
+    // may provide an invalid base64 string.
+    let decoded_bytes = BASE64_STANDARD
+        .decode(user_credentials.trim())
+        .ok()?;
 
     // Convert the decoded base64 bytes into a manageable
     // string.
-    let Ok(decoded_credentials) = String::from_utf8(decoded_bytes)
-    else {
-        return Ok(OptionalAuth::unauthenticated());
-    };
+    let decoded_credentials = String::from_utf8(decoded_bytes).ok()?;
 
     // Split the email and password, by standard they are
-    // not urlencoded, so colons should be avoided beware
-    // unwanted interferences.
-    let Some((email_cred, password_cred)) = decoded_credentials.split_once(':')
+    // not urlencoded, so only the first colon separates
+    // them and the password may contain colons.
+    let (email, password) = decoded_credentials.split_once(':')?;
+
+    Some((email.to_string(), password.to_string()))
+}
+
+/// Takes a "basic" authentication token, parsed
+/// by `parse_basic_credentials`, and authenticates
+/// it against the accounts table, the configured
+/// admin is synced there on startup.
+///
+/// Invalid credentials and locked accounts are
+/// treated as unauthenticated, failures count
+/// towards the `LockoutPolicy`.
+async fn try_authenticate_basic(
+    user_credentials: &str,
+    connection: &PgPool,
+    lockout: &LockoutPolicy,
+    jwt_keys: &JwtKeys
+) -> Result<OptionalAuth, OptionalAuthError> {
+    let Some((email_cred, password_cred)) = parse_basic_credentials(user_credentials)
     else {
         return Ok(OptionalAuth::unauthenticated());
     };
 
-    if email_cred != admin_email || password_cred != admin_password {
-        return Ok(OptionalAuth::unauthenticated());
-    }
+    let authentication = Account::get_by_auth(
+        connection,
+        AccountCredentials::Basic {
+            email: email_cred,
+            password: password_cred.into_bytes()
+        },
+        lockout
+    )
+        .await?;
+
+    let account = match authentication {
+        AuthenticationResult::Authenticated(account) => account,
+
+        // Locked accounts are responded to as wrong credentials,
+        // so unauthenticated clients can't tell which exist.
+        AuthenticationResult::Invalid
+            | AuthenticationResult::Locked => return Ok(OptionalAuth::unauthenticated())
+    };
 
     // In the case the credentials are correct, store a JWT.
+    let (jwt, jwt_claims) = issue_token(account.email(), jwt_keys)?;
+
+    Ok(OptionalAuth::authenticated(jwt, jwt_claims))
+}
 
+/// Issues a JWT for an email, expiring in
+/// `AUTH_EXPIRATION_HOURS`, with its claims.
+pub fn issue_token(
+    email: &str,
+    jwt_keys: &JwtKeys
+) -> Result<(String, OptionalAuthClaims), OptionalAuthError> {
     // Get a valid timestamp for when the JWT should expire,
     // the expiration time is defined in `AUTH_EXPIRATION_HOURS`.
     let expiration = Utc::now()
@@ -249,45 +409,225 @@ fn try_authenticate_basic(
     //
     // see: https://datatracker.ietf.org/doc/html/rfc7519
     let jwt_claims = OptionalAuthClaims {
-        email: admin_email.to_string(),
+        email: email.to_string(),
         //             i64 -> usize
         exp: expiration.try_into()
             .map_err(|_| OptionalAuthError::InvalidCast)?
     };
 
-    // Encode the JWT.
+    // Encode the JWT with the configured algorithm.
     let jwt = encode(
-        &Header::default(),
+        &Header::new(jwt_keys.algorithm()),
         &jwt_claims,
-        &EncodingKey::from_secret(get_jwt_secret()?.as_bytes())
+        jwt_keys.encoding()
     )?;
 
-    Ok(OptionalAuth::authenticated(jwt))
+    Ok((jwt, jwt_claims))
+}
+
+/// Decodes and validates a JWT, returning its claims
+/// or why it was rejected, tokens are only accepted
+/// while the account they were issued for exists.
+///
+/// This is the single decoding path for bearer tokens,
+/// shared by authentication and token introspection.
+pub async fn decode_token(
+    connection: &PgPool,
+    token: &str,
+    jwt_keys: &JwtKeys
+) -> Result<Result<OptionalAuthClaims, TokenRejection>, AccountError> {
+    let decode_result = match decode::<OptionalAuthClaims>(
+        token,
+        jwt_keys.decoding(),
+        &Validation::new(jwt_keys.algorithm())
+    ) {
+        Ok(decode_result) => decode_result,
+        Err(error) => return Ok(Err(error.into()))
+    };
+
+    // If the email contained by the JWT doesn't belong
+    // to an account take the JWT as invalid, i.e the
+    // account was deleted after the token was issued.
+    if Account::get_by_email(connection, &decode_result.claims.email)
+        .await?
+        .is_none()
+    {
+        return Ok(Err(TokenRejection::UnknownEmail));
+    }
+
+    Ok(Ok(decode_result.claims))
 }
 
 /// Takes a "bearer" authentication token, i.e a JWT
-/// if the decryption is successful and the email matches,
-/// an authenticated response is returned.
-fn try_authenticate_bearer(
+/// if the decryption is successful and the account
+/// exists, an authenticated response is returned.
+async fn try_authenticate_bearer(
     token: &str,
-    admin_email: &str
+    connection: &PgPool,
+    jwt_keys: &JwtKeys
 ) -> Result<OptionalAuth, OptionalAuthError> {
     // Decode the token into claims or return
     // unauthenticated if unsuccessful.
-    let Ok(decode_result) = decode::<OptionalAuthClaims>(
-        &token,
-        &DecodingKey::from_secret(get_jwt_secret()?.as_bytes()),
-        &Validation::new(Algorithm::HS256)
-    )
+    let Ok(claims) = decode_token(connection, token, jwt_keys).await?
     else {
         return Ok(OptionalAuth::unauthenticated());
     };
 
-    // If the email contained by the JWT is not
-    // the admin email take the JWT as invalid.
-    if decode_result.claims.email != admin_email {
-        return Ok(OptionalAuth::unauthenticated());
+    Ok(OptionalAuth::authenticated(token.to_string(), claims))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::accounts::PasswordPolicy;
+    use crate::utils::testing::{test_config, test_pool, TEST_PASSWORD};
+
+    fn encode_credentials(credentials: &[u8]) -> String {
+        BASE64_STANDARD.encode(credentials)
+    }
+
+    #[test]
+    fn passwords_may_contain_colons() {
+        let credentials = encode_credentials(b"admin@host:pa:ss:");
+
+        assert_eq!(
+            parse_basic_credentials(&credentials),
+            Some(("admin@host".to_string(), "pa:ss:".to_string()))
+        );
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_ignored() {
+        let credentials = format!("  {}\t\n", encode_credentials(b"admin@host:pass"));
+
+        assert_eq!(
+            parse_basic_credentials(&credentials),
+            Some(("admin@host".to_string(), "pass".to_string()))
+        );
     }
 
-    Ok(OptionalAuth::authenticated(token.to_string()))
+    #[test]
+    fn invalid_base64_is_rejected() {
+        assert_eq!(parse_basic_credentials("not base64!"), None);
+    }
+
+    #[test]
+    fn invalid_utf8_is_rejected() {
+        let credentials = encode_credentials(b"admin@host:\xff\xfe");
+
+        assert_eq!(parse_basic_credentials(&credentials), None);
+    }
+
+    #[test]
+    fn credentials_without_a_colon_are_rejected() {
+        let credentials = encode_credentials(b"admin@host");
+
+        assert_eq!(parse_basic_credentials(&credentials), None);
+    }
+
+    /// Creates an account with `TEST_PASSWORD`,
+    /// returning its email.
+    async fn insert_account(connection: &PgPool) -> String {
+        let email = format!("test-{}@reddyt.test", rand::random::<u32>());
+
+        Account::create_account(
+            connection,
+            AccountCredentials::Basic {
+                email: email.clone(),
+                password: TEST_PASSWORD.as_bytes().to_vec()
+            },
+            &PasswordPolicy::from_config(&test_config(&[]))
+        )
+            .await
+            .unwrap();
+
+        email
+    }
+
+    /// Authenticates basic credentials as the
+    /// extractor does, with the test configuration.
+    async fn authenticate_basic(connection: &PgPool, email: &str, password: &str) -> OptionalAuth {
+        let config = test_config(&[]);
+
+        try_authenticate_basic(
+            &encode_credentials(format!("{email}:{password}").as_bytes()),
+            connection,
+            &LockoutPolicy::from_config(&config),
+            &JwtKeys::from_config(&config).unwrap()
+        )
+            .await
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+    async fn accounts_authenticate_with_their_stored_password() {
+        let connection = test_pool().await;
+        let email = insert_account(&connection).await;
+
+        let auth = authenticate_basic(&connection, &email, TEST_PASSWORD).await;
+
+        assert_eq!(auth.claims().map(OptionalAuthClaims::email), Some(email.as_str()));
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+    async fn wrong_passwords_are_unauthenticated() {
+        let connection = test_pool().await;
+        let email = insert_account(&connection).await;
+
+        let auth = authenticate_basic(&connection, &email, "wrong-password-1").await;
+
+        assert!(!auth.is_authenticated());
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+    async fn tokens_of_existing_accounts_are_accepted() {
+        let connection = test_pool().await;
+        let email = insert_account(&connection).await;
+        let jwt_keys = JwtKeys::from_config(&test_config(&[])).unwrap();
+
+        let (token, _) = issue_token(&email, &jwt_keys).unwrap();
+        let auth = try_authenticate_bearer(&token, &connection, &jwt_keys)
+            .await
+            .unwrap();
+
+        assert_eq!(auth.claims().map(OptionalAuthClaims::email), Some(email.as_str()));
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+    async fn tokens_of_unknown_accounts_are_rejected() {
+        let connection = test_pool().await;
+        let jwt_keys = JwtKeys::from_config(&test_config(&[])).unwrap();
+
+        let (token, _) = issue_token("unknown@reddyt.test", &jwt_keys).unwrap();
+        let rejection = decode_token(&connection, &token, &jwt_keys)
+            .await
+            .unwrap();
+
+        assert_eq!(rejection.err(), Some(TokenRejection::UnknownEmail));
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires a database migrated with atlas at DATABASE_URL"]
+    async fn locked_accounts_are_unauthenticated_like_wrong_passwords() {
+        let connection = test_pool().await;
+        let email = insert_account(&connection).await;
+
+        // The default threshold locks after 5 failures.
+        for _ in 0..5 {
+            authenticate_basic(&connection, &email, "wrong-password-1").await;
+        }
+
+        let account = Account::get_by_email(&connection, &email)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(account.is_locked());
+
+        let auth = authenticate_basic(&connection, &email, TEST_PASSWORD).await;
+        assert!(!auth.is_authenticated());
+    }
 }
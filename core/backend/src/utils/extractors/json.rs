@@ -0,0 +1,160 @@
+use actix_web::error::{Error as ActixError, JsonPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::web::JsonConfig;
+use thiserror::Error;
+
+use crate::utils::application::errors::{http_error, ErrorCode, HttpError};
+
+/// Holds the errors a `Json` extractor may reject
+/// a request body with.
+#[derive(Error, Debug)]
+pub enum JsonBodyError {
+    #[error("The request body exceeds the limit of {0} bytes.")]
+    TooLarge(usize),
+
+    #[error("The request body is not valid JSON, {0}")]
+    Malformed(String),
+
+    #[error("The request body doesn't match the expected shape, {0}")]
+    InvalidShape(String),
+
+    #[error("The request body must be sent as application/json.")]
+    ContentType
+}
+
+impl HttpError for JsonBodyError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::TooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Malformed(_) | Self::InvalidShape(_) => StatusCode::BAD_REQUEST,
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::TooLarge(_) => ErrorCode::PayloadTooLarge,
+            Self::Malformed(_) => ErrorCode::MalformedJson,
+            Self::InvalidShape(_) => ErrorCode::InvalidBody,
+            Self::ContentType => ErrorCode::UnsupportedContentType
+        }
+    }
+}
+
+http_error!(JsonBodyError);
+
+/// Creates the `Json` extractor configuration, capping the
+/// body size to `limit` bytes and responding with the
+/// `json_formatter` envelope when the body is rejected.
+///
+/// Deserialization errors keep the `serde_json` message,
+/// which names the offending field for missing fields and
+/// the expected type for wrong-typed ones.
+///
+/// This must be registered as app data for every route
+/// taking a `Json` body to be covered.
+pub fn json_config(limit: usize) -> JsonConfig {
+    JsonConfig::default()
+        .limit(limit)
+        .error_handler(move |error, _request| match error {
+            JsonPayloadError::Overflow { .. }
+            | JsonPayloadError::OverflowKnownLength { .. } => JsonBodyError::TooLarge(limit).into(),
+
+            JsonPayloadError::Deserialize(error) if error.is_data() =>
+                JsonBodyError::InvalidShape(error.to_string()).into(),
+
+            JsonPayloadError::Deserialize(error) =>
+                JsonBodyError::Malformed(error.to_string()).into(),
+
+            JsonPayloadError::ContentType => JsonBodyError::ContentType.into(),
+
+            error => ActixError::from(error)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::ContentType;
+    use actix_web::http::StatusCode;
+    use actix_web::web::{post, Json};
+    use actix_web::{test, App, HttpResponse};
+    use serde::Deserialize;
+
+    use super::*;
+
+    /// The limit the test service is configured with.
+    const LIMIT: usize = 32;
+
+    #[derive(Deserialize)]
+    struct Payload {
+        #[allow(dead_code)]
+        name: String
+    }
+
+    #[derive(Deserialize)]
+    struct Envelope {
+        code: String,
+        error: String
+    }
+
+    async fn accept(_body: Json<Payload>) -> HttpResponse {
+        HttpResponse::NoContent().finish()
+    }
+
+    /// Posts `body` as JSON to a service taking a
+    /// `Payload`, returning the status and envelope.
+    async fn post_json(body: &'static str) -> (StatusCode, Envelope) {
+        let service = test::init_service(
+            App::new()
+                .app_data(json_config(LIMIT))
+                .route("/", post().to(accept))
+        )
+            .await;
+
+        let request = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_payload(body)
+            .to_request();
+
+        let response = test::call_service(&service, request).await;
+        let status = response.status();
+
+        (status, test::read_body_json(response).await)
+    }
+
+    #[actix_web::test]
+    async fn over_limit_bodies_are_rejected_with_the_envelope() {
+        let (status, envelope) = post_json(r#"{"name": "far longer than thirty two bytes"}"#).await;
+
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(envelope.code, "PAYLOAD_TOO_LARGE");
+        assert!(envelope.error.contains(&LIMIT.to_string()));
+    }
+
+    #[actix_web::test]
+    async fn missing_fields_are_invalid_bodies() {
+        let (status, envelope) = post_json("{}").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(envelope.code, "INVALID_BODY");
+        assert!(envelope.error.contains("name"));
+    }
+
+    #[actix_web::test]
+    async fn wrong_typed_fields_are_invalid_bodies() {
+        let (status, envelope) = post_json(r#"{"name": 1}"#).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(envelope.code, "INVALID_BODY");
+        assert!(envelope.error.contains("string"));
+    }
+
+    #[actix_web::test]
+    async fn syntax_errors_are_malformed_json() {
+        let (status, envelope) = post_json(r#"{"name": "#).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(envelope.code, "MALFORMED_JSON");
+    }
+}
@@ -0,0 +1,131 @@
+use std::future::{ready, Ready};
+
+use actix_web::dev::Payload;
+use actix_web::web::Query;
+use actix_web::{FromRequest, HttpRequest};
+use actix_web::http::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::utils::application::errors::{http_error, ErrorCode, HttpError};
+
+/// The amount of items returned when `limit` is not provided.
+pub const DEFAULT_LIST_LIMIT: i64 = 20;
+/// The maximum amount of items a single page may return.
+pub const MAX_LIST_LIMIT: i64 = 100;
+
+/// Holds any error that may occur while parsing
+/// list query parameters with `ListParams`.
+#[derive(Error, Debug)]
+pub enum ListParamsError {
+    #[error("Invalid query parameters, {0}")]
+    InvalidQuery(String),
+
+    #[error("The limit must be between 1 and {MAX_LIST_LIMIT}.")]
+    LimitOutOfBounds,
+
+    #[error("The offset must not be negative.")]
+    NegativeOffset
+}
+
+impl HttpError for ListParamsError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::InvalidQuery(_)
+                | Self::LimitOutOfBounds
+                | Self::NegativeOffset => StatusCode::BAD_REQUEST
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidQuery(_) => ErrorCode::InvalidQuery,
+            Self::LimitOutOfBounds => ErrorCode::LimitOutOfBounds,
+            Self::NegativeOffset => ErrorCode::NegativeOffset
+        }
+    }
+}
+
+http_error!(ListParamsError);
+
+/// The query parameters as sent by the client,
+/// before defaults and bounds are applied.
+#[derive(Deserialize, Debug)]
+struct RawListParams {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    before: Option<i32>
+}
+
+/// `ListParams` is an Actix Web extractor for the
+/// pagination query parameters shared by list endpoints.
+///
+/// The accepted parameters are
+/// - `limit`: how many items to return, between 1 and
+///   `MAX_LIST_LIMIT`, defaults to `DEFAULT_LIST_LIMIT`.
+/// - `offset`: how many items to skip, not negative,
+///   defaults to 0.
+/// - `before`: an optional id cursor, only items whose id
+///   is lower than this are returned, newest first.
+///
+/// Invalid values are rejected with a 400 formatted
+/// by `json_formatter` before reaching the route.
+#[derive(Debug, Clone, Copy)]
+pub struct ListParams {
+    limit: i64,
+    offset: i64,
+    before: Option<i32>
+}
+
+impl ListParams {
+    /// How many items to return.
+    #[inline]
+    pub fn limit(&self) -> i64 {
+        self.limit
+    }
+
+    /// How many items to skip.
+    #[inline]
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// The id cursor items must be older than.
+    #[inline]
+    pub fn before(&self) -> Option<i32> {
+        self.before
+    }
+}
+
+impl FromRequest for ListParams {
+    type Error = ListParamsError;
+
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(try_parse_list_params(req.query_string()))
+    }
+}
+
+/// Parses and validates the list parameters from a raw
+/// query string, applying the documented defaults.
+fn try_parse_list_params(query: &str) -> Result<ListParams, ListParamsError> {
+    let Query(raw) = Query::<RawListParams>::from_query(query)
+        .map_err(|error| ListParamsError::InvalidQuery(error.to_string()))?;
+
+    let limit = raw.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return Err(ListParamsError::LimitOutOfBounds);
+    }
+
+    let offset = raw.offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(ListParamsError::NegativeOffset);
+    }
+
+    Ok(ListParams {
+        limit,
+        offset,
+        before: raw.before
+    })
+}
@@ -1,2 +1,4 @@
 
 pub mod authentication;
+pub mod json;
+pub mod list;
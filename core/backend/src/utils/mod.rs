@@ -1,4 +1,5 @@
-
 pub mod application;
 pub mod extractors;
 pub mod external;
+#[cfg(test)]
+pub mod testing;
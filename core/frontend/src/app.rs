@@ -1,10 +1,17 @@
 use yew::prelude::*;
 
+use crate::components::auth::{AuthProvider, RequireAuth};
+use crate::components::dashboard::Dashboard;
+
 #[function_component(App)]
 pub fn app() -> Html {
     html! {
-        <main>
-            <h1>{ "Hello!!" }</h1>
-        </main>
+        <AuthProvider>
+            <main>
+                <RequireAuth>
+                    <Dashboard />
+                </RequireAuth>
+            </main>
+        </AuthProvider>
     }
 }
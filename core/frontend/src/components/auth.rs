@@ -0,0 +1,175 @@
+use gloo_net::http::Request;
+use serde::Deserialize;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::components::login::Login;
+
+/// The identity returned by `GET /authentication/me`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Identity {
+    pub email: String,
+    pub exp: usize
+}
+
+/// Whether the operator is authenticated, `Loading`
+/// until the backend answered the first time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthState {
+    Loading,
+    Anonymous,
+    Authenticated(Identity)
+}
+
+/// The authentication context shared trough `AuthProvider`.
+///
+/// Components receiving a 401 must call `expire`, so
+/// the login is rendered instead of a broken page.
+#[derive(Clone, PartialEq)]
+pub struct AuthContext {
+    state: AuthState,
+    refresh: Callback<()>,
+    expire: Callback<()>,
+    logout: Callback<()>
+}
+
+impl AuthContext {
+    /// The current authentication state.
+    pub fn state(&self) -> &AuthState {
+        &self.state
+    }
+
+    /// Asks the backend for the session identity
+    /// again, i.e after logging in.
+    pub fn refresh(&self) {
+        self.refresh.emit(());
+    }
+
+    /// Drops the identity after the backend
+    /// rejected the session with a 401.
+    pub fn expire(&self) {
+        self.expire.emit(());
+    }
+
+    /// Removes the session cookie and drops the identity.
+    pub fn logout(&self) {
+        self.logout.emit(());
+    }
+}
+
+/// Obtains the session identity, None if the
+/// backend doesn't consider the session valid.
+async fn fetch_identity() -> Option<Identity> {
+    let response = Request::get("/api/authentication/me")
+        .send()
+        .await
+        .ok()?;
+
+    if !response.ok() {
+        return None;
+    }
+
+    response
+        .json()
+        .await
+        .ok()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct AuthProviderProps {
+    pub children: Html
+}
+
+/// Provides the `AuthContext` to its children, the
+/// identity is requested once when mounted.
+#[function_component(AuthProvider)]
+pub fn auth_provider(props: &AuthProviderProps) -> Html {
+    let state = use_state(|| AuthState::Loading);
+
+    let refresh = {
+        let state = state.clone();
+
+        Callback::from(move |_| {
+            let state = state.clone();
+
+            spawn_local(async move {
+                match fetch_identity().await {
+                    Some(identity) => state.set(AuthState::Authenticated(identity)),
+                    None => state.set(AuthState::Anonymous)
+                }
+            });
+        })
+    };
+
+    let expire = {
+        let state = state.clone();
+        Callback::from(move |_| state.set(AuthState::Anonymous))
+    };
+
+    // The session is dropped locally even if the request
+    // fails, the cookie expires on its own regardless.
+    let logout = {
+        let expire = expire.clone();
+
+        Callback::from(move |_| {
+            let expire = expire.clone();
+
+            spawn_local(async move {
+                let _ = Request::post("/api/authentication/logout")
+                    .send()
+                    .await;
+
+                expire.emit(());
+            });
+        })
+    };
+
+    {
+        let refresh = refresh.clone();
+        use_effect_with((), move |_| refresh.emit(()));
+    }
+
+    let context = AuthContext {
+        state: (*state).clone(),
+        refresh,
+        expire,
+        logout
+    };
+
+    html! {
+        <ContextProvider<AuthContext> {context}>
+            { props.children.clone() }
+        </ContextProvider<AuthContext>>
+    }
+}
+
+/// Obtains the `AuthContext`, this must be
+/// used under an `AuthProvider`.
+#[hook]
+pub fn use_auth() -> AuthContext {
+    use_context::<AuthContext>()
+        .expect("use_auth must be used under an AuthProvider")
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RequireAuthProps {
+    pub children: Html
+}
+
+/// Renders its children only for authenticated operators,
+/// anyone else gets the login instead, every admin page
+/// must be wrapped by this.
+#[function_component(RequireAuth)]
+pub fn require_auth(props: &RequireAuthProps) -> Html {
+    let auth = use_auth();
+
+    match auth.state() {
+        AuthState::Loading => html! {
+            <p class="auth-loading">{ "Loading..." }</p>
+        },
+
+        AuthState::Anonymous => html! { <Login /> },
+
+        AuthState::Authenticated(_) => props.children.clone()
+    }
+}
@@ -0,0 +1,54 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::auth::{use_auth, AuthState};
+use crate::components::run_timeline::RunTimeline;
+
+/// The admin panel landing page, it must be
+/// rendered under `RequireAuth`.
+///
+/// The run timeline is shown for the profile
+/// whose id is entered.
+#[function_component(Dashboard)]
+pub fn dashboard() -> Html {
+    let auth = use_auth();
+    let profile_id = use_state(|| None::<i32>);
+
+    let AuthState::Authenticated(identity) = auth.state() else {
+        return html! {};
+    };
+
+    let onlogout = {
+        let auth = auth.clone();
+        Callback::from(move |_: MouseEvent| auth.logout())
+    };
+
+    let onprofile = {
+        let profile_id = profile_id.clone();
+
+        Callback::from(move |event: Event| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+            profile_id.set(input.value().parse().ok());
+        })
+    };
+
+    html! {
+        <>
+            <header class="dashboard-header">
+                <span>{ format!("Signed in as {}", identity.email) }</span>
+                <button onclick={onlogout}>{ "Log out" }</button>
+            </header>
+
+            <main class="dashboard">
+                <label>
+                    { "Profile" }
+                    <input type="number" min="1" onchange={onprofile} />
+                </label>
+
+                if let Some(profile_id) = *profile_id {
+                    <RunTimeline {profile_id} />
+                }
+            </main>
+        </>
+    }
+}
@@ -0,0 +1,92 @@
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use gloo_net::http::Request;
+use web_sys::HtmlInputElement;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::components::auth::use_auth;
+
+/// Authenticates against `POST /authentication/login`
+/// with basic credentials, the backend sets the session
+/// cookie and the identity is refreshed afterwards.
+async fn login(email: &str, password: &str) -> Result<(), String> {
+    let credentials = BASE64_STANDARD.encode(format!("{email}:{password}"));
+
+    let response = Request::post("/api/authentication/login")
+        .header("Authorization", &format!("Basic {credentials}"))
+        .send()
+        .await
+        .map_err(|error| error.to_string())?;
+
+    match response.status() {
+        204 => Ok(()),
+        401 => Err("Invalid email or password.".to_string()),
+        status => Err(format!("The login failed with status {status}."))
+    }
+}
+
+/// The login form rendered by `RequireAuth`
+/// for unauthenticated operators.
+#[function_component(Login)]
+pub fn login_form() -> Html {
+    let auth = use_auth();
+    let email = use_node_ref();
+    let password = use_node_ref();
+    let error = use_state(|| None::<String>);
+    let pending = use_state(|| false);
+
+    let onsubmit = {
+        let email = email.clone();
+        let password = password.clone();
+        let error = error.clone();
+        let pending = pending.clone();
+
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+
+            let (Some(email), Some(password)) = (
+                email.cast::<HtmlInputElement>(),
+                password.cast::<HtmlInputElement>()
+            ) else {
+                return;
+            };
+
+            let auth = auth.clone();
+            let error = error.clone();
+            let pending = pending.clone();
+            pending.set(true);
+
+            spawn_local(async move {
+                match login(&email.value(), &password.value()).await {
+                    Ok(()) => auth.refresh(),
+                    Err(message) => error.set(Some(message))
+                }
+
+                pending.set(false);
+            });
+        })
+    };
+
+    html! {
+        <form class="login" {onsubmit}>
+            <h1>{ "reddyt" }</h1>
+
+            <label>
+                { "Email" }
+                <input ref={email} type="email" autocomplete="username" required=true />
+            </label>
+
+            <label>
+                { "Password" }
+                <input ref={password} type="password" autocomplete="current-password" required=true />
+            </label>
+
+            if let Some(error) = &*error {
+                <p class="login-error">{ error }</p>
+            }
+
+            <button type="submit" disabled={*pending}>{ "Log in" }</button>
+        </form>
+    }
+}
@@ -0,0 +1,5 @@
+
+pub mod auth;
+pub mod dashboard;
+pub mod login;
+pub mod run_timeline;
@@ -0,0 +1,329 @@
+use std::rc::Rc;
+
+use chrono::{DateTime, Utc};
+use gloo_net::http::Request;
+use serde::Deserialize;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::components::auth::use_auth;
+
+/// How many runs are requested per page.
+const PAGE_SIZE: usize = 20;
+
+/// A run as returned by `GET /profiles/{id}/runs`,
+/// only the fields the timeline renders.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct RunEntry {
+    id: i32,
+    error: Option<String>,
+    processing: Vec<String>,
+    started_at: DateTime<Utc>,
+    finished_at: Option<DateTime<Utc>>
+}
+
+/// The error envelope every backend error is sent with.
+#[derive(Deserialize, Debug)]
+struct ErrorEnvelope {
+    error: String
+}
+
+/// The state a run is in, derived the same way
+/// the backend does from `error` and `finished_at`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RunOutcome {
+    Running,
+    Finished,
+    Errored
+}
+
+impl RunEntry {
+    fn outcome(&self) -> RunOutcome {
+        match (&self.error, self.finished_at) {
+            (Some(_), _) => RunOutcome::Errored,
+            (None, Some(_)) => RunOutcome::Finished,
+            (None, None) => RunOutcome::Running
+        }
+    }
+
+    /// How long the run took, None while it's running.
+    fn duration(&self) -> Option<String> {
+        let seconds = (self.finished_at? - self.started_at).num_seconds();
+
+        Some(format!("{}m {}s", seconds / 60, seconds % 60))
+    }
+}
+
+impl RunOutcome {
+    /// The class the run is color-coded with.
+    fn class(&self) -> &'static str {
+        match self {
+            Self::Running => "run-running",
+            Self::Finished => "run-finished",
+            Self::Errored => "run-errored"
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Running => "Running",
+            Self::Finished => "Finished",
+            Self::Errored => "Errored"
+        }
+    }
+}
+
+/// The timeline state, pages are appended as
+/// they are loaded trough the `before` cursor.
+///
+/// The state belongs to `profile_id`, pages and
+/// failures of any other profile are discarded,
+/// they may arrive after the profile changed.
+#[derive(Debug, Default, PartialEq)]
+struct TimelineState {
+    profile_id: Option<i32>,
+    runs: Vec<RunEntry>,
+    loading: bool,
+    error: Option<String>,
+    exhausted: bool
+}
+
+/// Why a page couldn't be loaded.
+enum FetchError {
+    /// The session expired, the login must be shown.
+    Unauthorized,
+
+    /// Any other failure, with a message to show.
+    Message(String)
+}
+
+enum TimelineAction {
+    /// Starts over for another profile.
+    Reset(i32),
+    Loading,
+    Loaded(i32, Vec<RunEntry>),
+    Failed(i32, String)
+}
+
+impl Reducible for TimelineState {
+    type Action = TimelineAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut runs = self.runs.clone();
+
+        let state = match action {
+            TimelineAction::Reset(profile_id) => Self {
+                profile_id: Some(profile_id),
+                ..Self::default()
+            },
+
+            TimelineAction::Loading => Self {
+                profile_id: self.profile_id,
+                runs,
+                loading: true,
+                error: None,
+                exhausted: self.exhausted
+            },
+
+            TimelineAction::Loaded(profile_id, _)
+                | TimelineAction::Failed(profile_id, _) if self.profile_id != Some(profile_id) => return self,
+
+            TimelineAction::Loaded(profile_id, page) => {
+                let exhausted = page.len() < PAGE_SIZE;
+                runs.extend(page);
+
+                Self {
+                    profile_id: Some(profile_id),
+                    runs,
+                    loading: false,
+                    error: None,
+                    exhausted
+                }
+            }
+
+            TimelineAction::Failed(profile_id, error) => Self {
+                profile_id: Some(profile_id),
+                runs,
+                loading: false,
+                error: Some(error),
+                exhausted: self.exhausted
+            }
+        };
+
+        Rc::new(state)
+    }
+}
+
+/// Requests a page of runs older than `before`, errors
+/// are reduced to the message sent by the backend.
+async fn fetch_runs(profile_id: i32, before: Option<i32>) -> Result<Vec<RunEntry>, FetchError> {
+    let mut url = format!("/api/profiles/{profile_id}/runs?limit={PAGE_SIZE}");
+
+    if let Some(before) = before {
+        url.push_str(&format!("&before={before}"));
+    }
+
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|error| FetchError::Message(error.to_string()))?;
+
+    if response.status() == 401 {
+        return Err(FetchError::Unauthorized);
+    }
+
+    if !response.ok() {
+        return Err(FetchError::Message(
+            response
+                .json::<ErrorEnvelope>()
+                .await
+                .map(|envelope| envelope.error)
+                .unwrap_or_else(|_| format!("The request failed with status {}.", response.status()))
+        ));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|error| FetchError::Message(error.to_string()))
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RunTimelineProps {
+    pub profile_id: i32
+}
+
+/// The run history of a profile, newest first,
+/// each run is color-coded by its outcome and more
+/// runs are loaded on demand.
+#[function_component(RunTimeline)]
+pub fn run_timeline(props: &RunTimelineProps) -> Html {
+    let auth = use_auth();
+    let state = use_reducer(TimelineState::default);
+
+    let load_page = {
+        let state = state.dispatcher();
+        let profile_id = props.profile_id;
+
+        Callback::from(move |before: Option<i32>| {
+            let auth = auth.clone();
+            let state = state.clone();
+            state.dispatch(TimelineAction::Loading);
+
+            spawn_local(async move {
+                match fetch_runs(profile_id, before).await {
+                    Ok(page) => state.dispatch(TimelineAction::Loaded(profile_id, page)),
+                    Err(FetchError::Unauthorized) => auth.expire(),
+                    Err(FetchError::Message(error)) => state.dispatch(TimelineAction::Failed(profile_id, error))
+                }
+            });
+        })
+    };
+
+    {
+        let load_page = load_page.clone();
+        let state = state.dispatcher();
+
+        // The runs of the previous profile are dropped
+        // before the first page of the new one loads.
+        use_effect_with(props.profile_id, move |profile_id| {
+            state.dispatch(TimelineAction::Reset(*profile_id));
+            load_page.emit(None);
+        });
+    }
+
+    let load_more = {
+        let before = state.runs.last().map(|run| run.id);
+        load_page.reform(move |_: MouseEvent| before)
+    };
+
+    html! {
+        <section class="run-timeline">
+            if state.runs.is_empty() && !state.loading && state.error.is_none() {
+                <p class="run-timeline-empty">{ "This profile didn't run yet." }</p>
+            }
+
+            <ol>
+                { for state.runs.iter().map(render_run) }
+            </ol>
+
+            if let Some(error) = &state.error {
+                <p class="run-timeline-error">{ error }</p>
+            }
+
+            if state.loading {
+                <p class="run-timeline-loading">{ "Loading runs..." }</p>
+            } else if !state.exhausted {
+                <button onclick={load_more}>{ "Load more" }</button>
+            }
+        </section>
+    }
+}
+
+fn render_run(run: &RunEntry) -> Html {
+    let outcome = run.outcome();
+
+    html! {
+        <li key={run.id} class={classes!("run", outcome.class())}>
+            <span class="run-state">{ outcome.label() }</span>
+            <time datetime={run.started_at.to_rfc3339()}>
+                { run.started_at.format("%Y-%m-%d %H:%M UTC").to_string() }
+            </time>
+
+            if let Some(duration) = run.duration() {
+                <span class="run-duration">{ duration }</span>
+            }
+
+            if outcome == RunOutcome::Running && !run.processing.is_empty() {
+                <span class="run-processing">{ run.processing.join(", ") }</span>
+            }
+
+            if let Some(error) = &run.error {
+                <pre class="run-error">{ error }</pre>
+            }
+        </li>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i32) -> RunEntry {
+        RunEntry {
+            id,
+            error: None,
+            processing: Vec::new(),
+            started_at: Utc::now(),
+            finished_at: Some(Utc::now())
+        }
+    }
+
+    fn reduce(state: Rc<TimelineState>, action: TimelineAction) -> Rc<TimelineState> {
+        Reducible::reduce(state, action)
+    }
+
+    #[test]
+    fn reset_drops_the_previous_profile_runs() {
+        let state = reduce(Rc::default(), TimelineAction::Reset(1));
+        let state = reduce(state, TimelineAction::Loaded(1, vec![entry(1), entry(2)]));
+        assert_eq!(state.runs.len(), 2);
+        assert!(state.exhausted);
+
+        let state = reduce(state, TimelineAction::Reset(2));
+        assert_eq!(*state, TimelineState { profile_id: Some(2), ..TimelineState::default() });
+    }
+
+    #[test]
+    fn responses_of_another_profile_are_ignored() {
+        let state = reduce(Rc::default(), TimelineAction::Reset(2));
+        let state = reduce(state, TimelineAction::Loading);
+
+        let stale = reduce(state.clone(), TimelineAction::Loaded(1, vec![entry(1)]));
+        assert!(stale.runs.is_empty());
+        assert!(stale.loading);
+
+        let stale = reduce(state, TimelineAction::Failed(1, "gone".into()));
+        assert_eq!(stale.error, None);
+    }
+}
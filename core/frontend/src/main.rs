@@ -8,6 +8,7 @@ use tracing_web::MakeWebConsoleWriter;
 use yew::Renderer;
 
 mod app;
+mod components;
 
 fn main() {
     let fmt_layer = tracing_layer()
@@ -30,4 +30,19 @@ table "accounts" {
 		null = false
 		comment = "The encrypted and salted account password."
 	}
+
+	# Reset on a successful authentication and when
+	# the account gets locked.
+	column "failed_attempts" {
+		type = int
+		null = false
+		default = 0
+		comment = "Consecutive failed authentications."
+	}
+
+	column "locked_until" {
+		type = timestamptz
+		null = true
+		comment = "Until when authentication is rejected, null if not locked."
+	}
 }
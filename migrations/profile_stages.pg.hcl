@@ -41,4 +41,13 @@ table "profile_stages" {
 		null = true
 		comment = "The last profile stage, if -1 its the first stage, if null its disconnected."
 	}
+
+	# Disabled stages stay in the chain, so the stages
+	# after them are still reached and run.
+	column "enabled" {
+		type = bool
+		null = false
+		default = true
+		comment = "Whether this stage is composed, disabled stages are skipped."
+	}
 }
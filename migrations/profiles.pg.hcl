@@ -1,3 +1,13 @@
+enum "video_container" {
+	schema = schema.reddyt
+	comment = "The output containers the compositor can produce."
+
+	values = [
+		"MP4",
+		"WEBM"
+	]
+}
+
 table "profiles" {
 	schema = schema.reddyt
 	comment = "The video profiles owned by an account."
@@ -12,12 +22,53 @@ table "profiles" {
 		on_delete = CASCADE
 	}
 
+	# Encoders require even dimensions for yuv420p output,
+	# the bounds cover everything up to 8K.
+	check "c_output_resolution" {
+		expr = "output_width BETWEEN 16 AND 7680 AND output_height BETWEEN 16 AND 7680 AND output_width % 2 = 0 AND output_height % 2 = 0"
+	}
+
+	# A loose BCP-47 shape, language followed by
+	# optional script, region or variant subtags.
+	check "c_locale_bcp47" {
+		expr = "locale ~ '^[A-Za-z]{2,3}(-[A-Za-z0-9]{2,8})*$'"
+	}
+
+	check "c_daily_run_cap" {
+		expr = "daily_run_cap IS NULL OR daily_run_cap >= 0"
+	}
+
+	check "c_schedule_jitter_secs" {
+		expr = "schedule_jitter_secs >= 0"
+	}
+
+	check "c_output_fps" {
+		expr = "output_fps BETWEEN 1 AND 120"
+	}
+
+	# YouTube accepts at most 500 characters across all tags,
+	# the title and description limits are the column sizes.
+	check "c_upload_tags_length" {
+		expr = "char_length(array_to_string(upload_tags_template, ',')) <= 500"
+	}
+
 	index "u_project_name_account_id" {
 		unique = true
 		columns = [column.account_id, column.name]
 		comment = "Unique index defining unique profile names for each account."
 	}
 
+	# The scheduler only reads unpaused profiles, with most
+	# of 20000 profiles paused `Profile::list_unpaused` shows
+	# a Seq Scan on profiles with `Filter: (NOT paused)` in
+	# EXPLAIN, and an Index Scan on this index with it.
+	# `Profile::count` reads every row either way.
+	index "i_profiles_unpaused" {
+		columns = [column.id]
+		where = "NOT paused"
+		comment = "Lookups of profiles the scheduler runs."
+	}
+
 	column "id" {
 		type = serial
 		null = false
@@ -47,6 +98,22 @@ table "profiles" {
 		comment = "A cron schedule to define when a video should be generated and uploaded."
 	}
 
+	# The schedule is evaluated in this timezone and
+	# converted back to UTC when stored.
+	column "timezone" {
+		type = varchar(64)
+		null = false
+		default = "UTC"
+		comment = "The IANA timezone the schedule is evaluated in."
+	}
+
+	column "locale" {
+		type = varchar(35)
+		null = false
+		default = "en-US"
+		comment = "The BCP-47 language tag generated text and narration are in."
+	}
+
 	column "paused" {
 		type = bool
 		null = false
@@ -54,6 +121,39 @@ table "profiles" {
 		comment = "Whether the schedule is paused and no videos should be generated."
 	}
 
+	# Set when the schedule is valid but never fires, such as
+	# `0 0 30 2 *`, the profile isn't paused for it.
+	column "schedule_warning" {
+		type = bool
+		null = false
+		default = false
+		comment = "Whether the schedule is valid but never fires."
+	}
+
+	column "priority" {
+		type = int
+		null = false
+		default = 0
+		comment = "Profiles with a higher priority run first when they are due at the same time."
+	}
+
+	# Spreads profiles sharing a cron expression, the offset
+	# is derived from the profile id so it stays stable.
+	column "schedule_jitter_secs" {
+		type = int
+		null = false
+		default = 0
+		comment = "The maximum delay in seconds applied to every scheduled run."
+	}
+
+	# Guards against flooding a channel with a misconfigured
+	# schedule, resets at the profile timezone midnight.
+	column "daily_run_cap" {
+		type = int
+		null = true
+		comment = "The maximum amount of runs per day, if null there is no cap."
+	}
+
 	column "ar_height" {
 		type = int
 		null = false
@@ -65,4 +165,75 @@ table "profiles" {
 		null = false
 		comment = "The aspect ratio width for the video."
 	}
+
+	# Defaults to a vertical short form video, long form
+	# profiles should be created with the dimensions swapped.
+	column "output_width" {
+		type = int
+		null = false
+		default = 1080
+		comment = "The rendered video width in pixels."
+	}
+
+	column "output_height" {
+		type = int
+		null = false
+		default = 1920
+		comment = "The rendered video height in pixels."
+	}
+
+	column "output_fps" {
+		type = int
+		null = false
+		default = 30
+		comment = "The rendered video framerate."
+	}
+
+	column "container" {
+		type = enum.video_container
+		null = false
+		default = "MP4"
+		comment = "The rendered video container format."
+	}
+
+	# Upload metadata templates accept `{{variable}}`
+	# placeholders, rendered for every upload.
+	column "upload_title_template" {
+		type = varchar(100)
+		null = false
+		default = "{{question}}"
+		comment = "The upload title template, at most YouTube's 100 characters."
+	}
+
+	column "upload_description_template" {
+		type = varchar(5000)
+		null = false
+		default = ""
+		comment = "The upload description template, at most YouTube's 5000 characters."
+	}
+
+	column "upload_tags_template" {
+		type = sql("VARCHAR(500)[]")
+		null = false
+		default = sql("'{}'")
+		comment = "The upload tag templates, at most 500 characters in total."
+	}
+
+	# This must be bumped by every mutating query on
+	# the profile, it's used for caching and conflict detection.
+	column "updated_at" {
+		type = timestamptz
+		null = false
+		default = sql("NOW()")
+		comment = "When was this profile last modified."
+	}
+
+	# Updates must filter by the version the client read
+	# and increment it, zero affected rows means a conflict.
+	column "version" {
+		type = int
+		null = false
+		default = 0
+		comment = "Optimistic locking version, incremented on every update."
+	}
 }
@@ -0,0 +1,57 @@
+table "run_usage" {
+	schema = schema.reddyt
+	comment = "What each run consumed from external providers and compute."
+
+	primary_key {
+		columns = [column.run_id]
+	}
+
+	foreign_key "fk_run_usage_run" {
+		columns = [column.run_id]
+		ref_columns = [table.runs.column.id]
+		on_delete = CASCADE
+	}
+
+	column "run_id" {
+		type = int
+		null = false
+		comment = "The run this usage belongs to."
+	}
+
+	column "text_tokens" {
+		type = bigint
+		null = false
+		default = 0
+		comment = "The text generation tokens, prompt and output."
+	}
+
+	column "tts_characters" {
+		type = bigint
+		null = false
+		default = 0
+		comment = "The characters sent to the TTS provider."
+	}
+
+	column "render_seconds" {
+		type = double_precision
+		null = false
+		default = 0
+		comment = "The compute time spent rendering the video."
+	}
+
+	column "upload_bytes" {
+		type = bigint
+		null = false
+		default = 0
+		comment = "The bytes sent to upload platforms."
+	}
+
+	# Providers that don't report usage are estimated,
+	# a single estimated step marks the whole record.
+	column "estimated" {
+		type = bool
+		null = false
+		default = false
+		comment = "Whether any of the figures is an estimate."
+	}
+}
@@ -1,3 +1,15 @@
+enum "run_error_kind" {
+	schema = schema.reddyt
+	comment = "What kind of failure made a run error."
+
+	values = [
+		"TRANSIENT",
+		"CONFIGURATION",
+		"PROVIDER",
+		"INTERNAL"
+	]
+}
+
 table "runs" {
 	schema = schema.reddyt
 	comment = "A run history for scheduling, feedback and preservation."
@@ -12,6 +24,29 @@ table "runs" {
 		on_delete = CASCADE
 	}
 
+	index "i_runs_started_at" {
+		columns = [column.started_at]
+		comment = "Range scans over the run history for analytics."
+	}
+
+	# Active runs are a handful while finished ones grow
+	# forever, `Run::count_active` otherwise shows a Seq
+	# Scan on runs with `Filter: finished_at IS NULL` in
+	# EXPLAIN, the partial index only holds active runs.
+	index "i_runs_active" {
+		columns = [column.finished_at]
+		where = "finished_at IS NULL"
+		comment = "Lookups of runs still in progress."
+	}
+
+	# Per profile aggregates such as `Profile::stats` and
+	# the daily run cap filter on profile_id, EXPLAIN shows
+	# a Seq Scan on runs without this.
+	index "i_runs_profile_started_at" {
+		columns = [column.profile_id, column.started_at]
+		comment = "Per profile run history lookups."
+	}
+
 	column "id" {
 		type = int
 		null = false
@@ -29,25 +64,53 @@ table "runs" {
 		comment = "Debug information in case of error, otherwise null."
 	}
 
+	# Set together with the error, retries and analytics
+	# key off this rather than the free form message.
+	column "error_kind" {
+		type = enum.run_error_kind
+		null = true
+		comment = "The category of the error, null if the run didn't error."
+	}
+
 	column "processing" {
 		type = sql("VARCHAR(128)[]")
 		null = false
 		comment = "The layers currently being processed named as \"stage.layer\""
 	}
 
+	# Stored by the text generation step, so runs can be
+	# replayed and deduplicated without generating again.
+	column "generated_question" {
+		type = text
+		null = true
+		comment = "The generated question, null until text generation finished."
+	}
+
+	column "generated_answer" {
+		type = text
+		null = true
+		comment = "The generated answer, null until text generation finished."
+	}
+
+	column "work_dir" {
+		type = varchar(1024)
+		null = true
+		comment = "Where the run intermediate artifacts are written, for debugging."
+	}
+
 	# This is used by the scheduler to know if a profile
 	# scheduled time is already started.
 	column "started_at" {
-		type = date
+		type = timestamptz
 		null = false
-		default = "NOW()"
+		default = sql("NOW()")
 		comment = "When did the processing for this run started."
 	}
 
 	# If this is not defined the status will be "running", otherwise
 	# error or finished depending on the error column.
 	column "finished_at" {
-		type = date
+		type = timestamptz
 		null = true
 		comment = "When did the processing for this run end."
 	}
@@ -18,6 +18,12 @@ table "uploads" {
 		on_delete = CASCADE
 	}
 
+	# YouTube rejects a publishAt that is not in the future,
+	# so it must be after the moment the video was uploaded.
+	check "c_publish_at_future" {
+		expr = "publish_at IS NULL OR publish_at > uploaded_at"
+	}
+
 	column "id" {
 		type = int
 		null = false
@@ -42,9 +48,17 @@ table "uploads" {
 	}
 
 	column "uploaded_at" {
-		type = date
+		type = timestamptz
 		null = false
-		default = "NOW()"
+		default = sql("NOW()")
 		comment = "When was this uploaded at."
 	}
+
+	# Uploads with a publish time are sent as private with
+	# YouTube's publishAt, which publishes them at this time.
+	column "publish_at" {
+		type = timestamptz
+		null = true
+		comment = "When is the upload scheduled to be published, null if published on upload."
+	}
 }